@@ -1,163 +1,44 @@
-use anyhow::Result as AnyResult;
-use std::convert::TryFrom;
+use adventofcode::intcode::{
+    evaluate_symbolic, search_space, solve, Machine, SearchCell, DEFAULT_MAX_STEPS,
+};
+use anyhow::{anyhow, Result as AnyResult};
+use std::env;
 use std::fs;
-use thiserror::Error;
+use std::ops::RangeInclusive;
 
-#[derive(Debug, Error)]
-enum MyError {
-    #[error("encountered unknown opcode `{0}`")]
-    UnknownOpCode(i64),
-    #[error("index out of bounds `{0}`")]
-    OutOfBounds(usize),
-}
-
-#[derive(Debug)]
-enum OpCode {
-    Halt,
-    Add,
-    Mul,
-}
-
-impl TryFrom<&i64> for OpCode {
-    type Error = MyError;
-    fn try_from(i: &i64) -> Result<Self, Self::Error> {
-        match *i {
-            99 => Ok(OpCode::Halt),
-            1 => Ok(OpCode::Add),
-            2 => Ok(OpCode::Mul),
-            _ => Err(MyError::UnknownOpCode(*i)),
-        }
-    }
-}
-
-trait IOperation {
-    fn is_halt(&self) -> bool {
-        false
-    }
-    fn execute(self, memory: &mut [i64]);
-}
-
-#[derive(Debug)]
-struct AddOperation {
-    src1: usize,
-    src2: usize,
-    dest: usize,
-}
-
-impl IOperation for AddOperation {
-    fn execute(self, memory: &mut [i64]) {
-        memory[self.dest] = memory[self.src1] + memory[self.src2];
-    }
-}
-
-#[derive(Debug)]
-struct MulOperation {
-    src1: usize,
-    src2: usize,
-    dest: usize,
-}
-
-impl IOperation for MulOperation {
-    fn execute(self, memory: &mut [i64]) {
-        memory[self.dest] = memory[self.src1] * memory[self.src2];
+/// Parses `a..=b` or `a..b` into an inclusive range.
+fn parse_range(s: &str) -> AnyResult<RangeInclusive<i64>> {
+    let bad_range = || anyhow!("invalid range `{}`, expected `a..=b` or `a..b`", s);
+    if let Some(i) = s.find("..=") {
+        Ok(s[..i].parse()?..=s[i + 3..].parse()?)
+    } else if let Some(i) = s.find("..") {
+        let end: i64 = s[i + 2..].parse()?;
+        Ok(s[..i].parse()?..=end.checked_sub(1).ok_or_else(bad_range)?)
+    } else {
+        Err(bad_range())
     }
 }
 
-#[derive(Debug)]
-struct HaltOperation;
-
-impl IOperation for HaltOperation {
-    fn is_halt(&self) -> bool {
-        true
-    }
-    fn execute(self, _: &mut [i64]) {
-        panic!("Can't execute Halt")
-    }
-}
-
-#[derive(Debug)]
-enum Operation {
-    Halt(HaltOperation),
-    Add(AddOperation),
-    Mul(MulOperation),
-}
-
-impl IOperation for Operation {
-    fn is_halt(&self) -> bool {
-        match self {
-            Self::Halt(ref inner) => inner.is_halt(),
-            Self::Add(ref inner) => inner.is_halt(),
-            Self::Mul(ref inner) => inner.is_halt(),
-        }
-    }
-    fn execute(self, memory: &mut [i64]) {
-        match self {
-            Self::Halt(inner) => inner.execute(memory),
-            Self::Add(inner) => inner.execute(memory),
-            Self::Mul(inner) => inner.execute(memory),
-        }
-    }
-}
-
-fn op_at(memory: &[i64], index: usize) -> Result<Operation, MyError> {
-    let opcode = OpCode::try_from(memory.get(index).ok_or(MyError::OutOfBounds(index))?)?;
-    Ok(match opcode {
-        OpCode::Halt => Operation::Halt(HaltOperation),
-        OpCode::Add => Operation::Add(AddOperation {
-            src1: *memory
-                .get(index + 1)
-                .ok_or(MyError::OutOfBounds(index + 1))? as _,
-            src2: *memory
-                .get(index + 2)
-                .ok_or(MyError::OutOfBounds(index + 2))? as _,
-            dest: *memory
-                .get(index + 3)
-                .ok_or(MyError::OutOfBounds(index + 3))? as _,
-        }),
-        OpCode::Mul => Operation::Mul(MulOperation {
-            src1: *memory
-                .get(index + 1)
-                .ok_or(MyError::OutOfBounds(index + 1))? as _,
-            src2: *memory
-                .get(index + 2)
-                .ok_or(MyError::OutOfBounds(index + 2))? as _,
-            dest: *memory
-                .get(index + 3)
-                .ok_or(MyError::OutOfBounds(index + 3))? as _,
-        }),
-    })
-}
-
-fn run(mut memory: Vec<i64>, at1: i64, at2: i64) -> Result<i64, MyError> {
-    memory[1] = at1;
-    memory[2] = at2;
-    let mut i = 0;
-    loop {
-        let op = op_at(&memory, i)?;
-        if op.is_halt() {
-            break;
-        }
-        op.execute(&mut memory);
-        i += 4;
-    }
-    Ok(memory[0])
-}
-
+/// Usage: 02-2 [target] [noun range] [verb range] [max steps]
+///
+/// Attempts that run for more than the maximum number of instructions are
+/// taken to never halt.
 fn main() -> AnyResult<()> {
-    let memory: Vec<i64> = fs::read_to_string("input/02")?
-        .split(',')
-        .filter_map(|s| s.parse().ok())
-        .collect();
+    let machine: Machine = fs::read_to_string("input/02")?.parse()?;
+    let mut args = env::args().skip(1);
     #[allow(clippy::unreadable_literal)]
-    let expected = 19690720;
-    let mut answer = None;
-    for at1 in 0..100 {
-        for at2 in 0..100 {
-            if run(memory.clone(), at1, at2).ok() == Some(expected) {
-                answer = Some(at1 * 100 + at2);
-            }
-        }
-    }
+    let expected = args.next().map_or(Ok(19690720), |s| s.parse())?;
+    let nouns = args.next().map_or(Ok(0..=99), |s| parse_range(&s))?;
+    let verbs = args.next().map_or(Ok(0..=99), |s| parse_range(&s))?;
+    let max_steps = args.next().map_or(Ok(DEFAULT_MAX_STEPS), |s| s.parse())?;
+    if let Ok(p) = evaluate_symbolic(machine.memory(), &[1, 2], 0) {
+        println!("memory[0] = {}", p);
+    }
+    let cells = [SearchCell::new(1, nouns), SearchCell::new(2, verbs)];
+    if search_space(&cells).is_none() {
+        return Err(anyhow!("too many nouns and verbs to search"));
+    }
+    let answer = solve(&machine, &cells, 0, expected, max_steps).map(|nv| nv[0] * 100 + nv[1]);
     println!("{:?}", answer);
     Ok(())
 }
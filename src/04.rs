@@ -0,0 +1,47 @@
+use adventofcode::password::{parse_rules, Format, PART_ONE, PART_TWO};
+use anyhow::{anyhow, Result as AnyResult};
+use std::env;
+
+/// Usage: 04 [--radix R] [--length N] [--list] [low-high] [rule...]
+///
+/// Counts the passwords in the range that meet all rules, or lists them with
+/// `--list`. The bounds are written in the radix, and passwords are padded
+/// with leading zeros to the length. Only decimal passwords default to the
+/// puzzle's range. `part1` and `part2` stand for the rules
+/// of either part of the puzzle, which is also what runs by default. See
+/// `password::parse_rule` for the other rules.
+fn main() -> AnyResult<()> {
+    let mut args = env::args().skip(1);
+    let (mut radix, mut length, mut list) = (10, 1, false);
+    let mut range = None;
+    let mut rules = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = |flag| args.next().ok_or_else(|| anyhow!("{} needs a value", flag));
+        match arg.as_str() {
+            "--radix" => radix = value("--radix")?.parse()?,
+            "--length" => length = value("--length")?.parse()?,
+            "--list" => list = true,
+            "part1" => rules.extend(PART_ONE.iter().map(|&r| r.to_owned())),
+            "part2" => rules.extend(PART_TWO.iter().map(|&r| r.to_owned())),
+            _ if range.is_none() => range = Some(arg),
+            _ => rules.push(arg),
+        }
+    }
+    if rules.is_empty() {
+        rules.extend(PART_TWO.iter().map(|&r| r.to_owned()));
+    }
+    let format = Format::new(radix, length)?;
+    let range = match range {
+        Some(range) => format.parse_range(&range)?,
+        None if radix == 10 => format.parse_range("273025-767253")?,
+        None => return Err(anyhow!("a range is needed with --radix {}", radix)),
+    };
+    let rule = parse_rules(rules.iter().map(String::as_str))?;
+    let passwords = format.passwords(range, &rule);
+    if list {
+        passwords.for_each(|p| println!("{}", p));
+    } else {
+        println!("{}", passwords.count());
+    }
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use adventofcode::intcode::{assemble, to_program_string};
+use anyhow::{anyhow, Result as AnyResult};
+use std::env;
+use std::fs;
+
+fn main() -> AnyResult<()> {
+    let path = env::args()
+        .nth(1)
+        .ok_or_else(|| anyhow!("usage: intcode-asm <source>"))?;
+    let program = assemble(&fs::read_to_string(path)?)?;
+    println!("{}", to_program_string(&program));
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use adventofcode::intcode::{assemble, Machine, State};
+use anyhow::Result as AnyResult;
+use std::env;
+use std::time::{Duration, Instant};
+
+/// Sums 3 * i for i = n down to 1, touching every kind of parameter mode.
+const PROGRAM: &str = "
+            IN -> [n]
+            ARB sum
+    loop:   MUL [n] 3 -> [term]
+            ADD [rb+0] [term] -> [rb+0]
+            ADD [n] -1 -> [n]
+            LT 0 [n] -> [more]
+            JT [more] loop
+            OUT [sum]
+            HLT
+    n:      .data 0
+    term:   .data 0
+    more:   .data 0
+    sum:    .data 0
+";
+
+fn bench(program: &[i64], n: i64, predecode: bool) -> AnyResult<(i64, Duration)> {
+    let mut machine = Machine::new(program.to_vec());
+    machine.set_predecode(predecode);
+    machine.push_input(n);
+    let start = Instant::now();
+    assert_eq!(machine.run()?, State::Halted);
+    Ok((machine.take_output()[0], start.elapsed()))
+}
+
+fn main() -> AnyResult<()> {
+    let n = env::args().nth(1).map_or(Ok(1_000_000), |s| s.parse())?;
+    let program = assemble(PROGRAM)?;
+    let (plain_result, plain) = bench(&program, n, false)?;
+    let (cached_result, cached) = bench(&program, n, true)?;
+    assert_eq!(plain_result, cached_result);
+    println!("Result: {}", plain_result);
+    println!("Interpreted: {:?}", plain);
+    println!("Predecoded:  {:?}", cached);
+    println!(
+        "Speedup: {:.2}x",
+        plain.as_secs_f64() / cached.as_secs_f64()
+    );
+    Ok(())
+}
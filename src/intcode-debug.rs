@@ -0,0 +1,19 @@
+use adventofcode::intcode::{Debugger, Machine};
+use anyhow::{anyhow, Result as AnyResult};
+use std::env;
+use std::fs;
+use std::io;
+
+fn main() -> AnyResult<()> {
+    let mut args = env::args().skip(1);
+    let path = args
+        .next()
+        .ok_or_else(|| anyhow!("usage: intcode-debug <program> [input]..."))?;
+    let mut machine: Machine = fs::read_to_string(path)?.parse()?;
+    for value in args {
+        machine.push_input(value.parse()?);
+    }
+    let stdin = io::stdin();
+    Debugger::new(machine).run(stdin.lock(), io::stdout())?;
+    Ok(())
+}
@@ -0,0 +1,11 @@
+use adventofcode::intcode::{disassemble, parse_program};
+use anyhow::Result as AnyResult;
+use std::env;
+use std::fs;
+
+fn main() -> AnyResult<()> {
+    let path = env::args().nth(1).unwrap_or_else(|| "input/02".to_owned());
+    let memory = parse_program(&fs::read_to_string(path)?)?;
+    print!("{}", disassemble(&memory));
+    Ok(())
+}
@@ -0,0 +1,379 @@
+use super::Mode;
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AsmError {
+    #[error("line {line}: unknown mnemonic `{mnemonic}`")]
+    UnknownMnemonic { line: usize, mnemonic: String },
+    #[error("line {line}: invalid operand `{operand}`")]
+    InvalidOperand { line: usize, operand: String },
+    #[error("line {line}: `{mnemonic}` takes {expected} operands, found {found}")]
+    OperandCount {
+        line: usize,
+        mnemonic: String,
+        expected: usize,
+        found: usize,
+    },
+    #[error("line {line}: destination of `{mnemonic}` can't be immediate")]
+    ImmediateDestination { line: usize, mnemonic: String },
+    #[error("line {line}: invalid label `{label}`")]
+    InvalidLabel { line: usize, label: String },
+    #[error("line {line}: unknown label `{label}`")]
+    UnknownLabel { line: usize, label: String },
+    #[error("line {line}: label `{label}` is already defined")]
+    DuplicateLabel { line: usize, label: String },
+    #[error("line {line}: address annotation `{found}` doesn't match address {expected}")]
+    AddressMismatch {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// Mnemonic, opcode, number of operands and whether the last operand is written to.
+const MNEMONICS: &[(&str, i64, usize, bool)] = &[
+    ("HLT", 99, 0, false),
+    ("ADD", 1, 3, true),
+    ("MUL", 2, 3, true),
+    ("IN", 3, 1, true),
+    ("OUT", 4, 1, false),
+    ("JT", 5, 2, false),
+    ("JF", 6, 2, false),
+    ("LT", 7, 3, true),
+    ("EQ", 8, 3, true),
+    ("ARB", 9, 1, false),
+];
+
+#[derive(Debug)]
+enum Value {
+    Number(i64),
+    Label(String),
+}
+
+#[derive(Debug)]
+struct Operand {
+    mode: Mode,
+    value: Value,
+}
+
+#[derive(Debug)]
+enum Item {
+    Instruction {
+        line: usize,
+        opcode: i64,
+        operands: Vec<Operand>,
+    },
+    Data {
+        line: usize,
+        values: Vec<Value>,
+    },
+}
+
+fn is_label(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_value(s: &str) -> Option<Value> {
+    let s = s.trim();
+    if let Ok(i) = s.parse() {
+        Some(Value::Number(i))
+    } else if is_label(s) {
+        Some(Value::Label(s.to_owned()))
+    } else {
+        None
+    }
+}
+
+fn parse_operand(s: &str, line: usize) -> Result<Operand, AsmError> {
+    let invalid = || AsmError::InvalidOperand {
+        line,
+        operand: s.to_owned(),
+    };
+    let (mode, inner) = if s.starts_with('[') && s.ends_with(']') {
+        let inner = s[1..s.len() - 1].trim();
+        if inner == "rb" {
+            return Ok(Operand {
+                mode: Mode::Relative,
+                value: Value::Number(0),
+            });
+        }
+        let offset = inner.strip_prefix("rb").map(str::trim_start);
+        if let Some(offset) = offset.filter(|o| o.starts_with(['+', '-'])) {
+            let value = parse_value(&offset[1..]).ok_or_else(invalid)?;
+            let value = match (offset.starts_with('-'), value) {
+                (false, value) => value,
+                (true, Value::Number(n)) => Value::Number(n.checked_neg().ok_or_else(invalid)?),
+                (true, Value::Label(_)) => return Err(invalid()),
+            };
+            return Ok(Operand {
+                mode: Mode::Relative,
+                value,
+            });
+        }
+        (Mode::Position, inner)
+    } else {
+        (Mode::Immediate, s.strip_prefix('#').unwrap_or(s))
+    };
+    let value = parse_value(inner).ok_or_else(invalid)?;
+    Ok(Operand { mode, value })
+}
+
+/// Splits a line at whitespace and commas, keeping bracketed operands together.
+fn tokenize(s: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    for c in s.chars() {
+        match c {
+            '[' => {
+                depth += 1;
+                current.push(c);
+            }
+            ']' => {
+                depth -= 1;
+                current.push(c);
+            }
+            c if depth == 0 && (c.is_whitespace() || c == ',') => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Assembles a program written in the listing syntax of the disassembler.
+///
+/// Each line holds optional `label:` definitions followed by an instruction
+/// or a `.data` directive; `;` starts a comment. Operands are immediate
+/// (`5` or `#5`), position (`[5]`) or relative (`[rb+5]`, `[rb-5]` or
+/// `[rb]`), and labels may be used wherever a number is expected. A `->`
+/// before the destination is optional. Numeric labels like `12:` are address
+/// annotations and must match the address they appear at.
+pub fn assemble(source: &str) -> Result<Vec<i64>, AsmError> {
+    let mut labels = HashMap::new();
+    let mut items = Vec::new();
+    let mut address = 0;
+    for (i, text) in source.lines().enumerate() {
+        let line = i + 1;
+        let text = text.split(';').next().unwrap_or("");
+        let mut tokens = tokenize(text).into_iter().filter(|t| t != "->").peekable();
+        while let Some(label) = tokens.peek().and_then(|t| t.strip_suffix(':')) {
+            if let Ok(found) = label.parse::<usize>() {
+                if found != address {
+                    return Err(AsmError::AddressMismatch {
+                        line,
+                        expected: address,
+                        found,
+                    });
+                }
+            } else if !is_label(label) {
+                return Err(AsmError::InvalidLabel {
+                    line,
+                    label: label.to_owned(),
+                });
+            } else if labels.insert(label.to_owned(), address).is_some() {
+                return Err(AsmError::DuplicateLabel {
+                    line,
+                    label: label.to_owned(),
+                });
+            }
+            tokens.next();
+        }
+        let mnemonic = match tokens.next() {
+            Some(mnemonic) => mnemonic,
+            None => continue,
+        };
+        let operands: Vec<String> = tokens.collect();
+        if mnemonic == ".data" {
+            let values = operands
+                .iter()
+                .map(|s| {
+                    parse_value(s).ok_or_else(|| AsmError::InvalidOperand {
+                        line,
+                        operand: s.to_owned(),
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            address += values.len();
+            items.push(Item::Data { line, values });
+            continue;
+        }
+        let &(_, opcode, expected, writes) = MNEMONICS
+            .iter()
+            .find(|(m, ..)| m.eq_ignore_ascii_case(&mnemonic))
+            .ok_or_else(|| AsmError::UnknownMnemonic {
+                line,
+                mnemonic: mnemonic.clone(),
+            })?;
+        if operands.len() != expected {
+            return Err(AsmError::OperandCount {
+                line,
+                mnemonic,
+                expected,
+                found: operands.len(),
+            });
+        }
+        let operands = operands
+            .iter()
+            .map(|s| parse_operand(s, line))
+            .collect::<Result<Vec<_>, _>>()?;
+        if writes && operands.last().map(|o| o.mode) == Some(Mode::Immediate) {
+            return Err(AsmError::ImmediateDestination { line, mnemonic });
+        }
+        address += 1 + operands.len();
+        items.push(Item::Instruction {
+            line,
+            opcode,
+            operands,
+        });
+    }
+
+    let resolve = |value: &Value, line: usize| match value {
+        Value::Number(i) => Ok(*i),
+        Value::Label(label) => {
+            labels
+                .get(label)
+                .map(|&a| a as i64)
+                .ok_or_else(|| AsmError::UnknownLabel {
+                    line,
+                    label: label.to_owned(),
+                })
+        }
+    };
+    let mut program = Vec::with_capacity(address);
+    for item in items {
+        match item {
+            Item::Instruction {
+                line,
+                opcode,
+                operands,
+            } => {
+                let modes: i64 = operands
+                    .iter()
+                    .enumerate()
+                    .map(|(n, o)| {
+                        let digit = match o.mode {
+                            Mode::Position => 0,
+                            Mode::Immediate => 1,
+                            Mode::Relative => 2,
+                        };
+                        digit * 10_i64.pow(n as u32 + 2)
+                    })
+                    .sum();
+                program.push(opcode + modes);
+                for operand in operands {
+                    program.push(resolve(&operand.value, line)?);
+                }
+            }
+            Item::Data { line, values } => {
+                for value in values {
+                    program.push(resolve(&value, line)?);
+                }
+            }
+        }
+    }
+    Ok(program)
+}
+
+/// Formats a program in the comma separated format of the puzzle inputs.
+pub fn to_program_string(program: &[i64]) -> String {
+    program
+        .iter()
+        .map(i64::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{disassemble, parse_program, Machine, State};
+    use super::*;
+    use std::fs;
+    #[test]
+    fn countdown() {
+        let source = "
+            ; prints n, n-1, ..., 1
+                    IN -> [n]
+            loop:   OUT [n]
+                    ADD [n] #-1 -> [n]
+                    JT [n] loop
+                    HLT
+            n:      .data 0
+        ";
+        let program = assemble(source).unwrap();
+        assert_eq!(
+            to_program_string(&program),
+            "3,12,4,12,1001,12,-1,12,1005,12,2,99,0"
+        );
+        let mut machine = Machine::new(program);
+        machine.push_input(3);
+        assert_eq!(machine.run().unwrap(), State::Halted);
+        assert_eq!(machine.take_output(), vec![3, 2, 1]);
+    }
+    #[test]
+    fn relative_operands() {
+        let program = assemble("ARB 10\nADD [rb+1] [rb-2] -> [rb + 3]\nHLT").unwrap();
+        assert_eq!(program, vec![109, 10, 22201, 1, -2, 3, 99]);
+        let program = assemble("ADD [rb - 3] [rb] -> [rb+ -4]").unwrap();
+        assert_eq!(program, vec![22201, -3, 0, -4]);
+        assert!(assemble("OUT [rb - x]").is_err());
+    }
+    #[test]
+    fn round_trip_listing() {
+        let memory = parse_program(&fs::read_to_string("input/02").unwrap()).unwrap();
+        assert_eq!(assemble(&disassemble(&memory)).unwrap(), memory);
+        let odd = [1099, 10004, 0, 11101, 1, 1, 3, 99];
+        assert_eq!(assemble(&disassemble(&odd)).unwrap(), odd);
+    }
+    #[test]
+    fn errors() {
+        assert_eq!(
+            assemble("NOP"),
+            Err(AsmError::UnknownMnemonic {
+                line: 1,
+                mnemonic: "NOP".to_owned()
+            })
+        );
+        assert_eq!(
+            assemble("\nADD 1 2 3"),
+            Err(AsmError::ImmediateDestination {
+                line: 2,
+                mnemonic: "ADD".to_owned()
+            })
+        );
+        assert_eq!(
+            assemble("JT 1 nowhere"),
+            Err(AsmError::UnknownLabel {
+                line: 1,
+                label: "nowhere".to_owned()
+            })
+        );
+        assert_eq!(
+            assemble("a: HLT\na: HLT"),
+            Err(AsmError::DuplicateLabel {
+                line: 2,
+                label: "a".to_owned()
+            })
+        );
+        assert_eq!(
+            assemble("OUT"),
+            Err(AsmError::OperandCount {
+                line: 1,
+                mnemonic: "OUT".to_owned(),
+                expected: 1,
+                found: 0
+            })
+        );
+    }
+}
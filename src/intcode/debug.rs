@@ -0,0 +1,224 @@
+use super::{Machine, State};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "\
+commands:
+  s, step [n]         execute n instructions (default 1)
+  c, continue         run until a breakpoint, watch, halt or missing input
+  b, break <addr>     toggle a breakpoint, without address list them
+  w, watch <addr>     toggle a watch on a memory cell, without address list them
+  d, dump [addr [n]]  print n memory cells starting at addr
+  i, input <v>...     queue input values
+  r, regs             print instruction pointer, relative base and state
+  h, help             print this help
+  q, quit             leave the debugger";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    Quit,
+}
+
+/// An interactive step-debugger around a `Machine`.
+#[derive(Debug)]
+pub struct Debugger {
+    machine: Machine,
+    breakpoints: BTreeSet<usize>,
+    watches: BTreeMap<usize, i64>,
+}
+
+impl Debugger {
+    pub fn new(mut machine: Machine) -> Self {
+        machine.set_tracing(true);
+        Self {
+            machine,
+            breakpoints: BTreeSet::new(),
+            watches: BTreeMap::new(),
+        }
+    }
+    pub fn machine(&self) -> &Machine {
+        &self.machine
+    }
+    fn cell(&self, address: usize) -> i64 {
+        self.machine.memory().get(address).copied().unwrap_or(0)
+    }
+    /// Executes one instruction and reports what happened.
+    /// Returns whether execution may go on without stopping.
+    fn single_step<W: Write>(&mut self, out: &mut W, echo: bool) -> io::Result<bool> {
+        let state = match self.machine.step() {
+            Ok(state) => state,
+            Err(e) => {
+                writeln!(out, "error: {}", e)?;
+                return Ok(false);
+            }
+        };
+        let mut go_on = true;
+        if echo {
+            for entry in self.machine.take_trace() {
+                writeln!(out, "{}", entry)?;
+            }
+        } else {
+            self.machine.take_trace();
+        }
+        let output = self.machine.take_output();
+        if !output.is_empty() {
+            let output: Vec<_> = output.iter().map(i64::to_string).collect();
+            writeln!(out, "output: {}", output.join(" "))?;
+        }
+        let changed: Vec<_> = self
+            .watches
+            .iter()
+            .map(|(&address, &old)| (address, old, self.cell(address)))
+            .filter(|&(_, old, new)| old != new)
+            .collect();
+        for (address, old, new) in changed {
+            writeln!(out, "watch [{}]: {} -> {}", address, old, new)?;
+            self.watches.insert(address, new);
+            go_on = false;
+        }
+        match state {
+            State::Halted => {
+                writeln!(out, "halted")?;
+                go_on = false;
+            }
+            State::WaitingForInput => {
+                writeln!(out, "waiting for input")?;
+                go_on = false;
+            }
+            State::Running => {}
+        }
+        if self.breakpoints.contains(&self.machine.ip()) {
+            writeln!(out, "breakpoint at {}", self.machine.ip())?;
+            go_on = false;
+        }
+        Ok(go_on)
+    }
+    /// Parses and executes a single command line.
+    pub fn execute<W: Write>(&mut self, line: &str, out: &mut W) -> io::Result<Control> {
+        let mut words = line.split_whitespace();
+        let command = match words.next() {
+            Some(command) => command,
+            None => return Ok(Control::Continue),
+        };
+        let args: Result<Vec<i64>, _> = words.map(str::parse).collect();
+        let args = match args {
+            Ok(args) => args,
+            Err(e) => {
+                writeln!(out, "invalid argument: {}", e)?;
+                return Ok(Control::Continue);
+            }
+        };
+        let address = match (command, args.first()) {
+            ("b" | "break" | "w" | "watch" | "d" | "dump", Some(&a)) if a < 0 => {
+                writeln!(out, "invalid argument: {}", a)?;
+                return Ok(Control::Continue);
+            }
+            (_, a) => a.map(|&a| a as usize),
+        };
+        match command {
+            "s" | "step" => {
+                for _ in 0..args.first().copied().unwrap_or(1).max(1) {
+                    if !self.single_step(out, true)? {
+                        break;
+                    }
+                }
+            }
+            "c" | "continue" => while self.single_step(out, false)? {},
+            "b" | "break" => match address {
+                Some(a) if !self.breakpoints.remove(&a) => {
+                    self.breakpoints.insert(a);
+                }
+                Some(_) => {}
+                None => writeln!(out, "breakpoints: {:?}", self.breakpoints)?,
+            },
+            "w" | "watch" => match address {
+                Some(a) if self.watches.remove(&a).is_none() => {
+                    self.watches.insert(a, self.cell(a));
+                }
+                Some(_) => {}
+                None => writeln!(out, "watches: {:?}", self.watches)?,
+            },
+            "d" | "dump" => {
+                let start = address.unwrap_or(0);
+                let len = match args.get(1) {
+                    Some(&n) if n < 0 => {
+                        writeln!(out, "invalid argument: {}", n)?;
+                        return Ok(Control::Continue);
+                    }
+                    Some(&n) => n as usize,
+                    None => self.machine.memory().len(),
+                };
+                // only the cells that exist, which also bounds huge counts
+                let end = start.saturating_add(len).min(self.machine.memory().len());
+                for (i, chunk) in (start..end)
+                    .map(|a| self.cell(a))
+                    .collect::<Vec<_>>()
+                    .chunks(8)
+                    .enumerate()
+                {
+                    let cells: Vec<_> = chunk.iter().map(i64::to_string).collect();
+                    writeln!(out, "{:>5}: {}", start + i * 8, cells.join(" "))?;
+                }
+            }
+            "i" | "input" => args.iter().for_each(|&v| self.machine.push_input(v)),
+            "r" | "regs" => writeln!(
+                out,
+                "ip: {}  rb: {}  state: {:?}",
+                self.machine.ip(),
+                self.machine.relative_base(),
+                self.machine.state()
+            )?,
+            "q" | "quit" => return Ok(Control::Quit),
+            "h" | "help" => writeln!(out, "{}", HELP)?,
+            _ => writeln!(out, "unknown command `{}`, try `help`", command)?,
+        }
+        Ok(Control::Continue)
+    }
+    /// Reads commands from `input` until `quit` or end of input.
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut out: W) -> io::Result<()> {
+        write!(out, "(icdb) ")?;
+        out.flush()?;
+        for line in input.lines() {
+            if self.execute(&line?, &mut out)? == Control::Quit {
+                break;
+            }
+            write!(out, "(icdb) ")?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn session() {
+        let machine: Machine = "3,11,1001,11,1,11,4,11,1105,1,2,0".parse().unwrap();
+        let mut debugger = Debugger::new(machine);
+        let mut out = Vec::new();
+        let script = "s\ni 5\ns 2\nw 11\nc\nb 8\nc\nr\nd 8 4\nd 2 -1\nb -5\nw -5\nd -5\nd 10 9999999999\nq\ns\n";
+        debugger.run(script.as_bytes(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let expected = [
+            "(icdb) waiting for input",
+            "(icdb) (icdb)     0: IN -> [11]  ; [11] <- 5",
+            "    2: ADD [11] 1 -> [11]  ; reads 5 1  ; [11] <- 6",
+            "(icdb) (icdb) output: 6",
+            "watch [11]: 6 -> 7",
+            "(icdb) (icdb) output: 7",
+            "breakpoint at 8",
+            "(icdb) ip: 8  rb: 0  state: Running",
+            "(icdb)     8: 1105 1 2 7",
+            "(icdb) invalid argument: -1",
+            "(icdb) invalid argument: -5",
+            "(icdb) invalid argument: -5",
+            "(icdb) invalid argument: -5",
+            "(icdb)    10: 2 7",
+            "(icdb) ",
+        ];
+        assert_eq!(out, expected.join("\n"));
+        assert_eq!(debugger.machine().ip(), 8);
+    }
+}
@@ -0,0 +1,157 @@
+use super::{op_at, Mode, Operation, Param};
+use std::fmt;
+
+impl fmt::Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mode {
+            Mode::Position => write!(f, "[{}]", self.value),
+            Mode::Immediate => write!(f, "{}", self.value),
+            Mode::Relative if self.value < 0 => write!(f, "[rb{}]", self.value),
+            Mode::Relative => write!(f, "[rb+{}]", self.value),
+        }
+    }
+}
+
+impl Operation {
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::Halt(_) => "HLT",
+            Self::Add(_) => "ADD",
+            Self::Mul(_) => "MUL",
+            Self::Input(_) => "IN",
+            Self::Output(_) => "OUT",
+            Self::JumpIfTrue(_) => "JT",
+            Self::JumpIfFalse(_) => "JF",
+            Self::LessThan(_) => "LT",
+            Self::Equals(_) => "EQ",
+            Self::AdjustRelativeBase(_) => "ARB",
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let m = self.mnemonic();
+        match self {
+            Self::Halt(_) => write!(f, "{}", m),
+            Self::Add(op) => write!(f, "{} {} {} -> {}", m, op.src1, op.src2, op.dest),
+            Self::Mul(op) => write!(f, "{} {} {} -> {}", m, op.src1, op.src2, op.dest),
+            Self::LessThan(op) => write!(f, "{} {} {} -> {}", m, op.src1, op.src2, op.dest),
+            Self::Equals(op) => write!(f, "{} {} {} -> {}", m, op.src1, op.src2, op.dest),
+            Self::Input(op) => write!(f, "{} -> {}", m, op.dest),
+            Self::Output(op) => write!(f, "{} {}", m, op.src),
+            Self::JumpIfTrue(op) => write!(f, "{} {} {}", m, op.cond, op.target),
+            Self::JumpIfFalse(op) => write!(f, "{} {} {}", m, op.cond, op.target),
+            Self::AdjustRelativeBase(op) => write!(f, "{} {}", m, op.offset),
+        }
+    }
+}
+
+/// One entry of a listing, either a decoded instruction or a raw data word.
+#[derive(Debug)]
+pub enum Line {
+    Instruction(usize, Operation),
+    Data(usize, i64),
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Instruction(address, op) => write!(f, "{:>5}: {}", address, op),
+            Self::Data(address, value) => write!(f, "{:>5}: .data {}", address, value),
+        }
+    }
+}
+
+/// Walks memory from address 0, decoding instructions with `op_at`.
+/// Words that do not decode into a complete instruction are rendered as data.
+#[derive(Debug)]
+pub struct Disassembler<'a> {
+    memory: &'a [i64],
+    address: usize,
+}
+
+impl<'a> Disassembler<'a> {
+    pub fn new(memory: &'a [i64]) -> Self {
+        Self { memory, address: 0 }
+    }
+}
+
+/// Whether `value` is exactly how the assembler writes `op`: no mode digits
+/// beyond its parameters, and no destination in immediate mode, which
+/// couldn't execute.
+fn is_canonical(op: &Operation, value: i64) -> bool {
+    let dest = match op {
+        Operation::Add(o) => Some(o.dest),
+        Operation::Mul(o) => Some(o.dest),
+        Operation::LessThan(o) => Some(o.dest),
+        Operation::Equals(o) => Some(o.dest),
+        Operation::Input(o) => Some(o.dest),
+        _ => None,
+    };
+    value < 10_i64.pow(op.width() as u32 + 1) && dest.is_none_or(|d| d.mode != Mode::Immediate)
+}
+
+impl<'a> Iterator for Disassembler<'a> {
+    type Item = Line;
+    fn next(&mut self) -> Option<Self::Item> {
+        let address = self.address;
+        let value = *self.memory.get(address)?;
+        match op_at(self.memory, address) {
+            Ok(op) if address + op.width() <= self.memory.len() && is_canonical(&op, value) => {
+                self.address += op.width();
+                Some(Line::Instruction(address, op))
+            }
+            _ => {
+                self.address += 1;
+                Some(Line::Data(address, value))
+            }
+        }
+    }
+}
+
+/// Renders a full listing of `memory`, one line per instruction or data word.
+pub fn disassemble(memory: &[i64]) -> String {
+    Disassembler::new(memory)
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn listing() {
+        let memory = [1002, 4, 3, 4, 109, -2, 21101, 5, 6, 7, 204, 1, 42, 99];
+        let expected = [
+            "    0: MUL [4] 3 -> [4]",
+            "    4: ARB -2",
+            "    6: ADD 5 6 -> [rb+7]",
+            "   10: OUT [rb+1]",
+            "   12: .data 42",
+            "   13: HLT",
+        ];
+        assert_eq!(disassemble(&memory), expected.join("\n") + "\n");
+    }
+    #[test]
+    fn truncated_instruction_is_data() {
+        let lines: Vec<String> = Disassembler::new(&[99, 1, 0])
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(lines, ["    0: HLT", "    1: .data 1", "    2: .data 0"]);
+        // stray mode digits and immediate destinations are not instructions
+        let lines: Vec<String> = Disassembler::new(&[1099, 10004, 0, 11101, 1, 1, 3, 0])
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "    0: .data 1099",
+                "    1: .data 10004",
+                "    2: .data 0",
+                "    3: .data 11101",
+                "    4: ADD [1] [3] -> [0]",
+            ]
+        );
+    }
+}
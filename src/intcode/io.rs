@@ -0,0 +1,92 @@
+use super::MyError;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+use std::sync::mpsc::{Receiver, Sender};
+
+/// A source of values for the Intcode input instruction.
+pub trait Input {
+    /// Returns the next value, or `None` if no value is available yet.
+    /// The machine then pauses and can be resumed once input arrived.
+    fn read(&mut self) -> Option<i64>;
+}
+
+/// A sink for the values produced by the Intcode output instruction.
+pub trait Output {
+    fn write(&mut self, value: i64) -> Result<(), MyError>;
+}
+
+impl Input for VecDeque<i64> {
+    fn read(&mut self) -> Option<i64> {
+        self.pop_front()
+    }
+}
+
+impl Input for Receiver<i64> {
+    fn read(&mut self) -> Option<i64> {
+        self.try_recv().ok()
+    }
+}
+
+/// Blocks until a value arrives, for machines running on their own thread.
+/// Reports no input once all senders are gone.
+#[derive(Debug)]
+pub struct BlockingReceiver(pub Receiver<i64>);
+
+impl Input for BlockingReceiver {
+    fn read(&mut self) -> Option<i64> {
+        self.0.recv().ok()
+    }
+}
+
+/// Feeds the machine from any iterator of values.
+#[derive(Debug, Clone)]
+pub struct IterInput<T>(pub T);
+
+impl<T: Iterator<Item = i64>> Input for IterInput<T> {
+    fn read(&mut self) -> Option<i64> {
+        self.0.next()
+    }
+}
+
+/// Reads one value per line from stdin, blocking until a line is available.
+#[derive(Debug, Default)]
+pub struct StdinInput;
+
+impl Input for StdinInput {
+    fn read(&mut self) -> Option<i64> {
+        let mut line = String::new();
+        io::stdin().lock().read_line(&mut line).ok()?;
+        line.trim().parse().ok()
+    }
+}
+
+impl Output for Vec<i64> {
+    fn write(&mut self, value: i64) -> Result<(), MyError> {
+        self.push(value);
+        Ok(())
+    }
+}
+
+impl Output for VecDeque<i64> {
+    fn write(&mut self, value: i64) -> Result<(), MyError> {
+        self.push_back(value);
+        Ok(())
+    }
+}
+
+impl Output for Sender<i64> {
+    fn write(&mut self, value: i64) -> Result<(), MyError> {
+        self.send(value).map_err(|_| MyError::OutputClosed)
+    }
+}
+
+/// Prints one value per line to stdout.
+#[derive(Debug, Default)]
+pub struct StdoutOutput;
+
+impl Output for StdoutOutput {
+    fn write(&mut self, value: i64) -> Result<(), MyError> {
+        println!("{}", value);
+        Ok(())
+    }
+}
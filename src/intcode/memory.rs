@@ -0,0 +1,135 @@
+use super::MyError;
+use std::ops::{Deref, DerefMut};
+
+/// The memory limit of a new `Memory`, in cells, so that a runaway write
+/// fails instead of zero-filling gigabytes.
+pub const DEFAULT_LIMIT: usize = 1 << 24;
+
+/// Intcode memory that grows on demand.
+///
+/// Reads beyond the loaded image yield `0`, writes beyond it extend the image.
+/// The limit, `DEFAULT_LIMIT` unless set otherwise, turns addresses at or
+/// above it into `MyError::OutOfBounds`, as does a write that would need more
+/// memory than can be allocated. A limit of `None` lets memory grow freely.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Memory {
+    cells: Vec<i64>,
+    limit: Option<usize>,
+}
+
+impl Memory {
+    pub fn new(cells: Vec<i64>) -> Self {
+        Self {
+            cells,
+            limit: Some(DEFAULT_LIMIT),
+        }
+    }
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+    }
+    fn check(&self, address: usize) -> Result<(), MyError> {
+        match self.limit {
+            Some(limit) if address >= limit => Err(MyError::OutOfBounds(address)),
+            _ => Ok(()),
+        }
+    }
+    pub fn read(&self, address: usize) -> Result<i64, MyError> {
+        self.check(address)?;
+        Ok(self.cells.get(address).copied().unwrap_or(0))
+    }
+    /// Makes sure a write to `address` will succeed, allocating whatever it
+    /// needs without growing the image yet.
+    pub fn reserve(&mut self, address: usize) -> Result<(), MyError> {
+        self.check(address)?;
+        if address >= self.cells.len() {
+            let additional = address - self.cells.len() + 1;
+            self.cells
+                .try_reserve_exact(additional)
+                .map_err(|_| MyError::OutOfBounds(address))?;
+        }
+        Ok(())
+    }
+    pub fn write(&mut self, address: usize, value: i64) -> Result<(), MyError> {
+        self.reserve(address)?;
+        if address >= self.cells.len() {
+            self.cells.resize(address + 1, 0);
+        }
+        self.cells[address] = value;
+        Ok(())
+    }
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl Deref for Memory {
+    type Target = [i64];
+    fn deref(&self) -> &Self::Target {
+        &self.cells
+    }
+}
+
+impl DerefMut for Memory {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.cells
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn grows_on_write() {
+        let mut memory = Memory::new(vec![1, 2, 3]);
+        assert_eq!(memory.read(100).unwrap(), 0);
+        assert_eq!(memory.len(), 3);
+        memory.write(10, 7).unwrap();
+        assert_eq!(memory.len(), 11);
+        assert_eq!(memory.read(10).unwrap(), 7);
+        assert_eq!(memory.read(5).unwrap(), 0);
+    }
+    #[test]
+    fn respects_limit() {
+        let mut memory = Memory::new(vec![1, 2, 3]);
+        memory.set_limit(Some(8));
+        assert!(memory.write(7, 1).is_ok());
+        assert!(matches!(memory.write(8, 1), Err(MyError::OutOfBounds(8))));
+        assert!(matches!(memory.read(9), Err(MyError::OutOfBounds(9))));
+    }
+    #[test]
+    fn default_limit() {
+        let mut memory = Memory::new(vec![1, 2, 3]);
+        assert_eq!(memory.limit(), Some(DEFAULT_LIMIT));
+        assert!(matches!(
+            memory.write(1_000_000_000, 1),
+            Err(MyError::OutOfBounds(1_000_000_000))
+        ));
+        assert_eq!(memory.len(), 3);
+        assert!(memory.write(DEFAULT_LIMIT - 1, 1).is_ok());
+    }
+    #[test]
+    fn unallocatable_write() {
+        let mut memory = Memory::new(vec![1, 2, 3]);
+        memory.set_limit(None);
+        let address = 1 << 62;
+        assert!(matches!(
+            memory.write(address, 1),
+            Err(MyError::OutOfBounds(a)) if a == address
+        ));
+        assert_eq!(memory.len(), 3);
+
+        let mut machine: super::super::Machine = "1101,1,1,4611686018427387904,99".parse().unwrap();
+        match machine.run().unwrap_err() {
+            MyError::Instruction { error, .. } => {
+                assert!(matches!(*error, MyError::OutOfBounds(a) if a == address))
+            }
+            e => panic!("unexpected error {}", e),
+        }
+    }
+}
@@ -0,0 +1,868 @@
+mod asm;
+mod debug;
+mod disasm;
+mod io;
+mod memory;
+mod network;
+mod predecode;
+mod search;
+mod snapshot;
+mod symbolic;
+mod trace;
+
+pub use self::asm::{assemble, to_program_string, AsmError};
+pub use self::debug::{Control, Debugger};
+pub use self::disasm::{disassemble, Disassembler, Line};
+pub use self::io::{BlockingReceiver, Input, IterInput, Output, StdinInput, StdoutOutput};
+pub use self::memory::{Memory, DEFAULT_LIMIT};
+pub use self::network::{replay_inputs, Delivery, Message, Network, NetworkState, Route};
+use self::predecode::DecodeCache;
+pub use self::search::{
+    find_inputs, find_inputs_for_value, search_space, SearchCell, DEFAULT_MAX_STEPS,
+};
+pub use self::snapshot::{Snapshot, SnapshotError};
+pub use self::symbolic::{evaluate as evaluate_symbolic, solve, Polynomial, SymbolicError};
+pub use self::trace::TraceEntry;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::num::ParseIntError;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MyError {
+    #[error("encountered unknown opcode `{0}`")]
+    UnknownOpCode(i64),
+    #[error("index out of bounds `{0}`")]
+    OutOfBounds(usize),
+    #[error("encountered negative address `{0}`")]
+    NegativeAddress(i64),
+    #[error("encountered unknown parameter mode `{0}`")]
+    UnknownParameterMode(i64),
+    #[error("can't write to an immediate mode parameter")]
+    ImmediateWrite,
+    #[error("arithmetic overflow")]
+    Overflow,
+    #[error("output channel was closed")]
+    OutputClosed,
+    #[error("network route is not supported here")]
+    UnsupportedRoute,
+    #[error("no machine {0} in the network")]
+    UnknownNode(usize),
+    #[error("machine {node} failed: {error}")]
+    Node { node: usize, error: Box<MyError> },
+    #[error("instruction `{opcode}` at address {ip} failed: {error}")]
+    Instruction {
+        ip: usize,
+        opcode: i64,
+        error: Box<MyError>,
+    },
+}
+
+impl MyError {
+    fn at(self, ip: usize, opcode: i64) -> Self {
+        Self::Instruction {
+            ip,
+            opcode,
+            error: Box::new(self),
+        }
+    }
+    /// The underlying error, without the instruction it occurred at.
+    pub fn kind(&self) -> &Self {
+        match self {
+            Self::Instruction { error, .. } | Self::Node { error, .. } => error.kind(),
+            _ => self,
+        }
+    }
+}
+
+/// Converts a computed value into an address, rejecting negative values.
+fn to_address(value: i64) -> Result<usize, MyError> {
+    usize::try_from(value).map_err(|_| MyError::NegativeAddress(value))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpCode {
+    Halt,
+    Add,
+    Mul,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustRelativeBase,
+}
+
+impl TryFrom<&i64> for OpCode {
+    type Error = MyError;
+    fn try_from(i: &i64) -> Result<Self, Self::Error> {
+        match *i % 100 {
+            99 => Ok(OpCode::Halt),
+            1 => Ok(OpCode::Add),
+            2 => Ok(OpCode::Mul),
+            3 => Ok(OpCode::Input),
+            4 => Ok(OpCode::Output),
+            5 => Ok(OpCode::JumpIfTrue),
+            6 => Ok(OpCode::JumpIfFalse),
+            7 => Ok(OpCode::LessThan),
+            8 => Ok(OpCode::Equals),
+            9 => Ok(OpCode::AdjustRelativeBase),
+            _ => Err(MyError::UnknownOpCode(*i)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+impl TryFrom<i64> for Mode {
+    type Error = MyError;
+    fn try_from(i: i64) -> Result<Self, Self::Error> {
+        match i {
+            0 => Ok(Mode::Position),
+            1 => Ok(Mode::Immediate),
+            2 => Ok(Mode::Relative),
+            _ => Err(MyError::UnknownParameterMode(i)),
+        }
+    }
+}
+
+/// A single operand of an instruction, as it is stored in memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Param {
+    pub mode: Mode,
+    pub value: i64,
+}
+
+pub trait IOperation {
+    fn is_halt(&self) -> bool {
+        false
+    }
+    fn execute<I: Input, O: Output>(self, machine: &mut Machine<I, O>) -> Result<(), MyError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddOperation {
+    pub src1: Param,
+    pub src2: Param,
+    pub dest: Param,
+}
+
+impl IOperation for AddOperation {
+    fn execute<I: Input, O: Output>(self, machine: &mut Machine<I, O>) -> Result<(), MyError> {
+        let value = machine
+            .load(self.src1)?
+            .checked_add(machine.load(self.src2)?)
+            .ok_or(MyError::Overflow)?;
+        machine.store(self.dest, value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MulOperation {
+    pub src1: Param,
+    pub src2: Param,
+    pub dest: Param,
+}
+
+impl IOperation for MulOperation {
+    fn execute<I: Input, O: Output>(self, machine: &mut Machine<I, O>) -> Result<(), MyError> {
+        let value = machine
+            .load(self.src1)?
+            .checked_mul(machine.load(self.src2)?)
+            .ok_or(MyError::Overflow)?;
+        machine.store(self.dest, value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputOperation {
+    pub dest: Param,
+}
+
+impl IOperation for InputOperation {
+    fn execute<I: Input, O: Output>(self, machine: &mut Machine<I, O>) -> Result<(), MyError> {
+        // a value taken from the input would be lost if the store failed
+        machine.destination(self.dest)?;
+        match machine.input.read() {
+            Some(value) => machine.store(self.dest, value),
+            None => {
+                machine.state = State::WaitingForInput;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputOperation {
+    pub src: Param,
+}
+
+impl IOperation for OutputOperation {
+    fn execute<I: Input, O: Output>(self, machine: &mut Machine<I, O>) -> Result<(), MyError> {
+        let value = machine.load(self.src)?;
+        machine.output.write(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JumpIfTrueOperation {
+    pub cond: Param,
+    pub target: Param,
+}
+
+impl IOperation for JumpIfTrueOperation {
+    fn execute<I: Input, O: Output>(self, machine: &mut Machine<I, O>) -> Result<(), MyError> {
+        if machine.load(self.cond)? != 0 {
+            machine.ip = to_address(machine.load(self.target)?)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JumpIfFalseOperation {
+    pub cond: Param,
+    pub target: Param,
+}
+
+impl IOperation for JumpIfFalseOperation {
+    fn execute<I: Input, O: Output>(self, machine: &mut Machine<I, O>) -> Result<(), MyError> {
+        if machine.load(self.cond)? == 0 {
+            machine.ip = to_address(machine.load(self.target)?)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LessThanOperation {
+    pub src1: Param,
+    pub src2: Param,
+    pub dest: Param,
+}
+
+impl IOperation for LessThanOperation {
+    fn execute<I: Input, O: Output>(self, machine: &mut Machine<I, O>) -> Result<(), MyError> {
+        let value = machine.load(self.src1)? < machine.load(self.src2)?;
+        machine.store(self.dest, value as _)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EqualsOperation {
+    pub src1: Param,
+    pub src2: Param,
+    pub dest: Param,
+}
+
+impl IOperation for EqualsOperation {
+    fn execute<I: Input, O: Output>(self, machine: &mut Machine<I, O>) -> Result<(), MyError> {
+        let value = machine.load(self.src1)? == machine.load(self.src2)?;
+        machine.store(self.dest, value as _)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdjustRelativeBaseOperation {
+    pub offset: Param,
+}
+
+impl IOperation for AdjustRelativeBaseOperation {
+    fn execute<I: Input, O: Output>(self, machine: &mut Machine<I, O>) -> Result<(), MyError> {
+        machine.relative_base = machine
+            .relative_base
+            .checked_add(machine.load(self.offset)?)
+            .ok_or(MyError::Overflow)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HaltOperation;
+
+impl IOperation for HaltOperation {
+    fn is_halt(&self) -> bool {
+        true
+    }
+    fn execute<I: Input, O: Output>(self, machine: &mut Machine<I, O>) -> Result<(), MyError> {
+        machine.state = State::Halted;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    Halt(HaltOperation),
+    Add(AddOperation),
+    Mul(MulOperation),
+    Input(InputOperation),
+    Output(OutputOperation),
+    JumpIfTrue(JumpIfTrueOperation),
+    JumpIfFalse(JumpIfFalseOperation),
+    LessThan(LessThanOperation),
+    Equals(EqualsOperation),
+    AdjustRelativeBase(AdjustRelativeBaseOperation),
+}
+
+impl Operation {
+    /// Number of memory cells the instruction occupies, including the opcode.
+    pub fn width(&self) -> usize {
+        match self {
+            Self::Halt(_) => 1,
+            Self::Input(_) | Self::Output(_) | Self::AdjustRelativeBase(_) => 2,
+            Self::JumpIfTrue(_) | Self::JumpIfFalse(_) => 3,
+            Self::Add(_) | Self::Mul(_) | Self::LessThan(_) | Self::Equals(_) => 4,
+        }
+    }
+}
+
+impl IOperation for Operation {
+    fn is_halt(&self) -> bool {
+        match self {
+            Self::Halt(ref inner) => inner.is_halt(),
+            Self::Add(ref inner) => inner.is_halt(),
+            Self::Mul(ref inner) => inner.is_halt(),
+            Self::Input(ref inner) => inner.is_halt(),
+            Self::Output(ref inner) => inner.is_halt(),
+            Self::JumpIfTrue(ref inner) => inner.is_halt(),
+            Self::JumpIfFalse(ref inner) => inner.is_halt(),
+            Self::LessThan(ref inner) => inner.is_halt(),
+            Self::Equals(ref inner) => inner.is_halt(),
+            Self::AdjustRelativeBase(ref inner) => inner.is_halt(),
+        }
+    }
+    fn execute<I: Input, O: Output>(self, machine: &mut Machine<I, O>) -> Result<(), MyError> {
+        match self {
+            Self::Halt(inner) => inner.execute(machine),
+            Self::Add(inner) => inner.execute(machine),
+            Self::Mul(inner) => inner.execute(machine),
+            Self::Input(inner) => inner.execute(machine),
+            Self::Output(inner) => inner.execute(machine),
+            Self::JumpIfTrue(inner) => inner.execute(machine),
+            Self::JumpIfFalse(inner) => inner.execute(machine),
+            Self::LessThan(inner) => inner.execute(machine),
+            Self::Equals(inner) => inner.execute(machine),
+            Self::AdjustRelativeBase(inner) => inner.execute(machine),
+        }
+    }
+}
+
+/// Reads a cell, treating everything beyond the slice as `0` like `Memory` does.
+fn cell_at(memory: &[i64], index: usize) -> i64 {
+    memory.get(index).copied().unwrap_or(0)
+}
+
+/// Decodes the `n`th parameter (starting at 1) of the instruction at `index`.
+fn param_at(memory: &[i64], index: usize, n: u32) -> Result<Param, MyError> {
+    let mode = Mode::try_from(cell_at(memory, index) / 10_i64.pow(n + 1) % 10)?;
+    let value = cell_at(memory, index + n as usize);
+    Ok(Param { mode, value })
+}
+
+/// Decodes the instruction at `index`.
+/// Errors are reported together with the address and raw opcode.
+pub fn op_at(memory: &[i64], index: usize) -> Result<Operation, MyError> {
+    decode(memory, index).map_err(|e| e.at(index, cell_at(memory, index)))
+}
+
+fn decode(memory: &[i64], index: usize) -> Result<Operation, MyError> {
+    let opcode = OpCode::try_from(&cell_at(memory, index))?;
+    Ok(match opcode {
+        OpCode::Halt => Operation::Halt(HaltOperation),
+        OpCode::Add => Operation::Add(AddOperation {
+            src1: param_at(memory, index, 1)?,
+            src2: param_at(memory, index, 2)?,
+            dest: param_at(memory, index, 3)?,
+        }),
+        OpCode::Mul => Operation::Mul(MulOperation {
+            src1: param_at(memory, index, 1)?,
+            src2: param_at(memory, index, 2)?,
+            dest: param_at(memory, index, 3)?,
+        }),
+        OpCode::Input => Operation::Input(InputOperation {
+            dest: param_at(memory, index, 1)?,
+        }),
+        OpCode::Output => Operation::Output(OutputOperation {
+            src: param_at(memory, index, 1)?,
+        }),
+        OpCode::JumpIfTrue => Operation::JumpIfTrue(JumpIfTrueOperation {
+            cond: param_at(memory, index, 1)?,
+            target: param_at(memory, index, 2)?,
+        }),
+        OpCode::JumpIfFalse => Operation::JumpIfFalse(JumpIfFalseOperation {
+            cond: param_at(memory, index, 1)?,
+            target: param_at(memory, index, 2)?,
+        }),
+        OpCode::LessThan => Operation::LessThan(LessThanOperation {
+            src1: param_at(memory, index, 1)?,
+            src2: param_at(memory, index, 2)?,
+            dest: param_at(memory, index, 3)?,
+        }),
+        OpCode::Equals => Operation::Equals(EqualsOperation {
+            src1: param_at(memory, index, 1)?,
+            src2: param_at(memory, index, 2)?,
+            dest: param_at(memory, index, 3)?,
+        }),
+        OpCode::AdjustRelativeBase => Operation::AdjustRelativeBase(AdjustRelativeBaseOperation {
+            offset: param_at(memory, index, 1)?,
+        }),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Running,
+    WaitingForInput,
+    Halted,
+}
+
+/// An Intcode computer holding its memory, instruction pointer and relative base.
+///
+/// By default input is taken from an in-memory queue and output is collected
+/// into a `Vec`, but any `Input` and `Output` implementation can be plugged in.
+#[derive(Debug, Clone)]
+pub struct Machine<I = VecDeque<i64>, O = Vec<i64>> {
+    memory: Memory,
+    ip: usize,
+    relative_base: i64,
+    state: State,
+    input: I,
+    output: O,
+    trace: Option<Vec<TraceEntry>>,
+    decoded: Option<DecodeCache>,
+}
+
+impl Machine {
+    pub fn new(program: Vec<i64>) -> Self {
+        Self::with_io(program, VecDeque::new(), Vec::new())
+    }
+    pub fn push_input(&mut self, value: i64) {
+        self.input.push_back(value);
+    }
+    pub fn take_output(&mut self) -> Vec<i64> {
+        std::mem::take(&mut self.output)
+    }
+}
+
+impl<I: Input, O: Output> Machine<I, O> {
+    pub fn with_io(program: Vec<i64>, input: I, output: O) -> Self {
+        Self {
+            memory: Memory::new(program),
+            ip: 0,
+            relative_base: 0,
+            state: State::Running,
+            input,
+            output,
+            trace: None,
+            decoded: None,
+        }
+    }
+    /// Replaces the I/O of the machine, returning the machine and the old I/O.
+    pub fn swap_io<I2: Input, O2: Output>(self, input: I2, output: O2) -> (Machine<I2, O2>, I, O) {
+        let machine = Machine {
+            memory: self.memory,
+            ip: self.ip,
+            relative_base: self.relative_base,
+            state: self.state,
+            input,
+            output,
+            trace: self.trace,
+            decoded: self.decoded,
+        };
+        (machine, self.input, self.output)
+    }
+    pub fn input(&self) -> &I {
+        &self.input
+    }
+    pub fn input_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+    pub fn output(&self) -> &O {
+        &self.output
+    }
+    pub fn output_mut(&mut self) -> &mut O {
+        &mut self.output
+    }
+    pub fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+    pub fn memory_mut(&mut self) -> &mut [i64] {
+        if let Some(decoded) = self.decoded.as_mut() {
+            decoded.clear();
+        }
+        &mut self.memory
+    }
+    /// Limits the addresses the program may access, see `Memory`.
+    pub fn set_memory_limit(&mut self, limit: Option<usize>) {
+        self.memory.set_limit(limit);
+    }
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+    pub fn state(&self) -> State {
+        self.state
+    }
+    /// Starts or stops recording a `TraceEntry` for every executed instruction.
+    pub fn set_tracing(&mut self, enabled: bool) {
+        self.trace = if enabled { Some(Vec::new()) } else { None };
+    }
+    /// Enables or disables running from a cache of compiled instructions.
+    /// This makes `run` several times faster for long running programs, at
+    /// the cost of memory proportional to the program. Tracing and `step`
+    /// always use the plain interpreter.
+    pub fn set_predecode(&mut self, enabled: bool) {
+        self.decoded = if enabled {
+            Some(DecodeCache::default())
+        } else {
+            None
+        };
+    }
+    /// Returns the entries recorded since the last call, keeping tracing enabled.
+    pub fn take_trace(&mut self) -> Vec<TraceEntry> {
+        self.trace.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+    fn address(&self, param: Param) -> Result<usize, MyError> {
+        match param.mode {
+            Mode::Relative => to_address(
+                self.relative_base
+                    .checked_add(param.value)
+                    .ok_or(MyError::Overflow)?,
+            ),
+            _ => to_address(param.value),
+        }
+    }
+    /// Resolves the value of a parameter according to its mode.
+    pub fn load(&self, param: Param) -> Result<i64, MyError> {
+        if param.mode == Mode::Immediate {
+            return Ok(param.value);
+        }
+        self.memory.read(self.address(param)?)
+    }
+    /// Writes `value` to the address a parameter refers to.
+    /// Resolves the address a parameter writes to and makes sure a store
+    /// there can succeed.
+    fn destination(&mut self, param: Param) -> Result<usize, MyError> {
+        if param.mode == Mode::Immediate {
+            return Err(MyError::ImmediateWrite);
+        }
+        let address = self.address(param)?;
+        self.memory.reserve(address)?;
+        Ok(address)
+    }
+    pub fn store(&mut self, param: Param, value: i64) -> Result<(), MyError> {
+        let address = self.destination(param)?;
+        self.memory.write(address, value)?;
+        if let Some(decoded) = self.decoded.as_mut() {
+            decoded.invalidate(address);
+        }
+        if let Some(entry) = self.trace.as_mut().and_then(|t| t.last_mut()) {
+            entry.writes.push((address, value));
+        }
+        Ok(())
+    }
+    /// Executes a single instruction. Stepping a halted machine is a no-op.
+    /// If the instruction needs input that is not available yet, the machine
+    /// stays at that instruction and reports `State::WaitingForInput`.
+    /// On error the instruction pointer is left at the faulting instruction.
+    pub fn step(&mut self) -> Result<State, MyError> {
+        if self.state == State::Halted {
+            return Ok(self.state);
+        }
+        self.state = State::Running;
+        let ip = self.ip;
+        let op = op_at(&self.memory, ip)?;
+        let entry = self.trace.as_ref().map(|_| TraceEntry {
+            ip,
+            op: op.clone(),
+            reads: op
+                .reads()
+                .into_iter()
+                .map_while(|p| self.load(p).ok())
+                .collect(),
+            writes: Vec::new(),
+        });
+        if let (Some(trace), Some(entry)) = (self.trace.as_mut(), entry) {
+            trace.push(entry);
+        }
+        if op.is_halt() {
+            self.state = State::Halted;
+            return Ok(self.state);
+        }
+        self.ip += op.width();
+        if let Err(e) = op.execute(self) {
+            self.ip = ip;
+            if let Some(trace) = self.trace.as_mut() {
+                trace.pop();
+            }
+            return Err(e.at(ip, cell_at(&self.memory, ip)));
+        }
+        if self.state == State::WaitingForInput {
+            self.ip = ip;
+            if let Some(trace) = self.trace.as_mut() {
+                trace.pop();
+            }
+        }
+        Ok(self.state)
+    }
+    /// Runs until the machine halts or waits for input.
+    pub fn run(&mut self) -> Result<State, MyError> {
+        if self.decoded.is_some() {
+            return self.run_predecoded();
+        }
+        while self.step()? == State::Running {}
+        Ok(self.state)
+    }
+    /// Like `run`, but executes at most `max_steps` instructions. The machine
+    /// is still `Running` if they ran out.
+    pub fn run_for(&mut self, max_steps: u64) -> Result<State, MyError> {
+        for _ in 0..max_steps {
+            if self.step()? != State::Running {
+                break;
+            }
+        }
+        Ok(self.state)
+    }
+}
+
+impl FromStr for Machine {
+    type Err = ParseIntError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(parse_program(s)?))
+    }
+}
+
+/// Parses a comma separated Intcode program.
+pub fn parse_program(s: &str) -> Result<Vec<i64>, ParseIntError> {
+    s.trim().split(',').map(|i| i.trim().parse()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn run_examples() {
+        let cases: &[(&str, &[i64])] = &[
+            ("1,0,0,0,99", &[2, 0, 0, 0, 99]),
+            ("2,3,0,3,99", &[2, 3, 0, 6, 99]),
+            ("2,4,4,5,99,0", &[2, 4, 4, 5, 99, 9801]),
+            ("1,1,1,4,99,5,6,0,99", &[30, 1, 1, 4, 2, 5, 6, 0, 99]),
+            (
+                "1,9,10,3,2,3,11,0,99,30,40,50",
+                &[3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50],
+            ),
+        ];
+        for (program, expected) in cases {
+            let mut machine: Machine = program.parse().unwrap();
+            assert_eq!(machine.run().unwrap(), State::Halted);
+            assert_eq!(machine.memory(), *expected);
+        }
+    }
+    #[test]
+    fn parameter_modes() {
+        let mut machine: Machine = "1002,4,3,4,33".parse().unwrap();
+        machine.run().unwrap();
+        assert_eq!(machine.memory(), &[1002, 4, 3, 4, 99]);
+        let mut machine: Machine = "1101,100,-1,4,0".parse().unwrap();
+        machine.run().unwrap();
+        assert_eq!(machine.memory()[4], 99);
+    }
+    #[test]
+    fn relative_base() {
+        let mut machine: Machine = "109,5,22201,2,3,4,99,10,20,0".parse().unwrap();
+        machine.run().unwrap();
+        assert_eq!(machine.relative_base(), 5);
+        assert_eq!(machine.memory()[9], 30);
+    }
+    #[test]
+    fn input_output() {
+        let mut machine: Machine = "3,0,4,0,3,0,4,0,99".parse().unwrap();
+        assert_eq!(machine.run().unwrap(), State::WaitingForInput);
+        assert_eq!(machine.ip(), 0);
+        machine.push_input(7);
+        assert_eq!(machine.run().unwrap(), State::WaitingForInput);
+        assert_eq!(machine.take_output(), vec![7]);
+        machine.push_input(-3);
+        assert_eq!(machine.run().unwrap(), State::Halted);
+        assert_eq!(machine.take_output(), vec![-3]);
+    }
+    #[test]
+    fn jumps_and_comparisons() {
+        let program = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,\
+                       1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,\
+                       999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+        for &(input, expected) in &[(7, 999), (8, 1000), (9, 1001)] {
+            let mut machine: Machine = program.parse().unwrap();
+            machine.push_input(input);
+            assert_eq!(machine.run().unwrap(), State::Halted);
+            assert_eq!(machine.take_output(), vec![expected]);
+        }
+        for &(program, input, expected) in &[
+            ("3,9,8,9,10,9,4,9,99,-1,8", 8, 1),
+            ("3,9,7,9,10,9,4,9,99,-1,8", 8, 0),
+            ("3,3,1108,-1,8,3,4,3,99", 3, 0),
+            ("3,3,1107,-1,8,3,4,3,99", 3, 1),
+            ("3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9", 0, 0),
+            ("3,3,1105,-1,9,1101,0,0,12,4,12,99,1", 5, 1),
+        ] {
+            let mut machine: Machine = program.parse().unwrap();
+            machine.push_input(input);
+            machine.run().unwrap();
+            assert_eq!(machine.take_output(), vec![expected]);
+        }
+    }
+    #[test]
+    fn memory_beyond_program() {
+        let program = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        let mut machine: Machine = program.parse().unwrap();
+        assert_eq!(machine.run().unwrap(), State::Halted);
+        assert_eq!(machine.take_output(), parse_program(program).unwrap());
+
+        let mut machine: Machine = "1101,1,1,1000,99".parse().unwrap();
+        machine.set_memory_limit(Some(1000));
+        let e = machine.run().unwrap_err();
+        assert!(matches!(e.kind(), MyError::OutOfBounds(1000)));
+    }
+    #[test]
+    fn runtime_errors() {
+        let cases = &[
+            ("1,0,0,0,42", 4, 42, "encountered unknown opcode `42`"),
+            (
+                "1,0,0,0,3101,0",
+                4,
+                3101,
+                "encountered unknown parameter mode `3`",
+            ),
+            ("1,-1,0,0,99", 0, 1, "encountered negative address `-1`"),
+            (
+                "1101,1,1,3,1105,1,-7",
+                4,
+                1105,
+                "encountered negative address `-7`",
+            ),
+            (
+                "11101,1,1,3,99",
+                0,
+                11101,
+                "can't write to an immediate mode parameter",
+            ),
+            (
+                "1102,4611686018427387904,2,0,99",
+                0,
+                1102,
+                "arithmetic overflow",
+            ),
+            (
+                "1101,1,1,4611686018427387904,99",
+                0,
+                1101,
+                "index out of bounds `4611686018427387904`",
+            ),
+        ];
+        for &(program, ip, opcode, message) in cases {
+            let mut machine: Machine = program.parse().unwrap();
+            machine.set_tracing(true);
+            match machine.run().unwrap_err() {
+                MyError::Instruction {
+                    ip: e_ip,
+                    opcode: e_opcode,
+                    ref error,
+                } => {
+                    assert_eq!((e_ip, e_opcode), (ip, opcode), "{}", program);
+                    assert_eq!(error.to_string(), message, "{}", program);
+                }
+                e => panic!("{}: unexpected error {}", program, e),
+            }
+            assert_eq!(machine.ip(), ip);
+            // the failed instruction is not part of the trace
+            assert!(machine.take_trace().iter().all(|entry| entry.ip != ip));
+        }
+    }
+    #[test]
+    fn failed_input_keeps_value() {
+        for program in &["3,-1,99", "103,0,99", "203,-1,99"] {
+            let mut machine: Machine = program.parse().unwrap();
+            machine.push_input(7);
+            assert!(machine.run().is_err(), "{}", program);
+            assert_eq!(machine.snapshot().input, vec![7], "{}", program);
+        }
+    }
+    #[test]
+    fn tracing() {
+        let mut machine: Machine = "3,9,1001,9,5,9,4,9,99,0".parse().unwrap();
+        machine.set_tracing(true);
+        machine.run().unwrap();
+        assert!(machine.take_trace().is_empty());
+        machine.push_input(2);
+        machine.run().unwrap();
+        let trace = machine.take_trace();
+        let ips: Vec<_> = trace.iter().map(|e| e.ip).collect();
+        assert_eq!(ips, vec![0, 2, 6, 8]);
+        assert_eq!(trace[0].writes, vec![(9, 2)]);
+        assert_eq!(trace[1].reads, vec![2, 5]);
+        assert_eq!(trace[1].writes, vec![(9, 7)]);
+        assert_eq!(trace[2].reads, vec![7]);
+        assert_eq!(
+            trace[1].to_string(),
+            "    2: ADD [9] 5 -> [9]  ; reads 2 5  ; [9] <- 7"
+        );
+    }
+    #[test]
+    fn predecode() {
+        let programs = [
+            "1,1,1,4,99,5,6,0,99",
+            "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,\
+             1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,\
+             999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99",
+            "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99",
+        ];
+        for &program in &programs {
+            for &input in &[7, 8, 9] {
+                let mut plain: Machine = program.parse().unwrap();
+                let mut cached = plain.clone();
+                cached.set_predecode(true);
+                plain.push_input(input);
+                cached.push_input(input);
+                assert_eq!(plain.run().unwrap(), cached.run().unwrap());
+                assert_eq!(plain.memory(), cached.memory());
+                assert_eq!(plain.take_output(), cached.take_output());
+            }
+        }
+        let mut machine: Machine = "1,0,0,0,99".parse().unwrap();
+        machine.set_predecode(true);
+        machine.memory_mut()[0] = 2;
+        machine.run().unwrap();
+        assert_eq!(machine.memory()[0], 4);
+        // errors from cached instructions match the interpreter
+        for &program in &["1002,9,2,9,1105,1,0,99,0,1", "1105,1,4611686018427387904"] {
+            let mut plain: Machine = program.parse().unwrap();
+            let mut cached = plain.clone();
+            cached.set_predecode(true);
+            let error = plain.run().unwrap_err().to_string();
+            assert_eq!(cached.run().unwrap_err().to_string(), error);
+            assert_eq!(plain.memory(), cached.memory());
+            assert_eq!(plain.ip(), cached.ip());
+        }
+    }
+    #[test]
+    fn channel_io() {
+        let (in_tx, in_rx) = std::sync::mpsc::channel();
+        let (out_tx, out_rx) = std::sync::mpsc::channel();
+        let program = parse_program("3,0,4,0,99").unwrap();
+        let mut machine = Machine::with_io(program, in_rx, out_tx);
+        assert_eq!(machine.run().unwrap(), State::WaitingForInput);
+        in_tx.send(42).unwrap();
+        assert_eq!(machine.run().unwrap(), State::Halted);
+        assert_eq!(out_rx.try_recv().unwrap(), 42);
+    }
+}
@@ -0,0 +1,391 @@
+use super::{BlockingReceiver, Machine, MyError, State};
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+/// Where the output of a machine goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// Every value is appended to the input of the given machine.
+    To(usize),
+    /// Values form `[address, x, y]` packets; `x` and `y` are delivered to the
+    /// machine at `address`, packets for unknown addresses leave the network.
+    Addressed,
+    /// Every value leaves the network.
+    External,
+}
+
+/// A value delivered to the input of a machine. Values queued from outside
+/// the network, including idle values, have no sender.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delivery {
+    pub from: Option<usize>,
+    pub to: usize,
+    pub value: i64,
+}
+
+/// Output that left the network: a single value for `Route::External`, or a
+/// whole packet including its address for `Route::Addressed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub from: usize,
+    pub values: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkState {
+    /// Every machine has halted.
+    Halted,
+    /// No machine can make progress without input from outside.
+    Idle,
+}
+
+/// Runs several Intcode machines and wires their outputs to each other.
+///
+/// `run` schedules the machines cooperatively in a fixed round-robin order,
+/// so a run is fully determined by the programs and the queued input. Every
+/// delivered value is logged, which allows replaying a single machine in
+/// isolation with `replay_inputs`.
+#[derive(Debug, Clone)]
+pub struct Network {
+    machines: Vec<Machine>,
+    routes: Vec<Route>,
+    packets: Vec<Vec<i64>>,
+    idle_input: Option<i64>,
+    external: Vec<Message>,
+    log: Vec<Delivery>,
+}
+
+impl Network {
+    /// Creates a network in which all output leaves the network.
+    pub fn new(machines: Vec<Machine>) -> Self {
+        let n = machines.len();
+        Self {
+            machines,
+            routes: vec![Route::External; n],
+            packets: vec![Vec::new(); n],
+            idle_input: None,
+            external: Vec::new(),
+            log: Vec::new(),
+        }
+    }
+    /// Connects the machines in order; the last one feeds back into the first
+    /// if `feedback` is set, and sends its output outside otherwise.
+    pub fn chain(machines: Vec<Machine>, feedback: bool) -> Self {
+        let mut network = Self::new(machines);
+        let n = network.machines.len();
+        for i in 0..n.saturating_sub(1) {
+            network.routes[i] = Route::To(i + 1);
+        }
+        if feedback && n > 0 {
+            network.routes[n - 1] = Route::To(0);
+        }
+        network
+    }
+    /// Fails with `MyError::UnknownNode` if `node` or the machine it is routed
+    /// to is not part of the network.
+    pub fn set_route(&mut self, node: usize, route: Route) -> Result<(), MyError> {
+        match route {
+            Route::To(to) if to >= self.machines.len() => Err(MyError::UnknownNode(to)),
+            _ if node >= self.machines.len() => Err(MyError::UnknownNode(node)),
+            _ => {
+                self.routes[node] = route;
+                Ok(())
+            }
+        }
+    }
+    /// Value a machine reads when it waits for input and none is queued,
+    /// e.g. `-1` for packet networks that poll.
+    pub fn set_idle_input(&mut self, value: Option<i64>) {
+        self.idle_input = value;
+    }
+    pub fn machines(&self) -> &[Machine] {
+        &self.machines
+    }
+    pub fn log(&self) -> &[Delivery] {
+        &self.log
+    }
+    pub fn take_external(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.external)
+    }
+    fn deliver(&mut self, from: Option<usize>, to: usize, value: i64) {
+        self.machines[to].push_input(value);
+        self.log.push(Delivery { from, to, value });
+    }
+    /// Queues input for a machine from outside the network.
+    pub fn push_input(&mut self, node: usize, value: i64) {
+        self.deliver(None, node, value);
+    }
+    fn route_output(&mut self, from: usize) {
+        for value in self.machines[from].take_output() {
+            match self.routes[from] {
+                Route::To(to) => self.deliver(Some(from), to, value),
+                Route::External => self.external.push(Message {
+                    from,
+                    values: vec![value],
+                }),
+                Route::Addressed => {
+                    self.packets[from].push(value);
+                    if self.packets[from].len() < 3 {
+                        continue;
+                    }
+                    let packet = std::mem::take(&mut self.packets[from]);
+                    match packet[0] {
+                        to if to >= 0 && (to as usize) < self.machines.len() => {
+                            self.deliver(Some(from), to as usize, packet[1]);
+                            self.deliver(Some(from), to as usize, packet[2]);
+                        }
+                        _ => self.external.push(Message {
+                            from,
+                            values: packet,
+                        }),
+                    }
+                }
+            }
+        }
+    }
+    /// Runs the machines round-robin, each until it halts or waits for input,
+    /// until all of them halted or a full round made no progress.
+    pub fn run(&mut self) -> Result<NetworkState, MyError> {
+        loop {
+            let mut progress = false;
+            for node in 0..self.machines.len() {
+                if self.machines[node].state() == State::Halted {
+                    continue;
+                }
+                let starved = self.machines[node].input.is_empty();
+                if starved && self.machines[node].state() == State::WaitingForInput {
+                    match self.idle_input {
+                        Some(value) => self.deliver(None, node, value),
+                        None => continue,
+                    }
+                } else {
+                    progress = true;
+                }
+                let sent = self.log.len() + self.external.len();
+                self.machines[node].run().map_err(|e| MyError::Node {
+                    node,
+                    error: Box::new(e),
+                })?;
+                self.route_output(node);
+                progress |= self.log.len() + self.external.len() > sent;
+            }
+            if self.machines.iter().all(|m| m.state() == State::Halted) {
+                return Ok(NetworkState::Halted);
+            }
+            if !progress {
+                return Ok(NetworkState::Idle);
+            }
+        }
+    }
+    /// Runs every machine on its own thread, connected by channels, until all
+    /// of them halted or can't receive input anymore. A cycle of machines that
+    /// all wait for each other blocks forever, use `run` for such networks.
+    ///
+    /// Only `Route::To` and `Route::External` are supported. Values that were
+    /// sent to a machine but never read stay queued in its input afterwards.
+    /// Deliveries between machines are not logged.
+    pub fn run_threaded(mut self) -> Result<Self, MyError> {
+        if let Some(node) = self.routes.iter().position(|&r| r == Route::Addressed) {
+            return Err(MyError::Node {
+                node,
+                error: Box::new(MyError::UnsupportedRoute),
+            });
+        }
+        let (senders, receivers): (Vec<Sender<i64>>, Vec<_>) =
+            self.machines.iter().map(|_| mpsc::channel()).unzip();
+        let (external_tx, external_rx) = mpsc::channel();
+        // queue all pending input before any machine starts sending
+        let machines: Vec<_> = std::mem::take(&mut self.machines)
+            .into_iter()
+            .zip(receivers)
+            .enumerate()
+            .map(|(node, (machine, receiver))| {
+                let (machine, queued, _) = machine.swap_io(BlockingReceiver(receiver), Vec::new());
+                for value in queued {
+                    senders[node]
+                        .send(value)
+                        .expect("receiver is owned by the machine");
+                }
+                machine
+            })
+            .collect();
+        let handles: Vec<_> = machines
+            .into_iter()
+            .enumerate()
+            .map(|(node, mut machine)| {
+                let target = match self.routes[node] {
+                    Route::To(to) => Some(senders[to].clone()),
+                    _ => None,
+                };
+                let external_tx = external_tx.clone();
+                thread::spawn(move || loop {
+                    let state = machine.step().map_err(|e| MyError::Node {
+                        node,
+                        error: Box::new(e),
+                    })?;
+                    for value in machine.output_mut().drain(..) {
+                        // the receiving machine may already have halted
+                        if let Some(tx) = &target {
+                            let _ = tx.send(value);
+                        } else {
+                            let _ = external_tx.send((node, value));
+                        }
+                    }
+                    if state != State::Running {
+                        return Ok(machine);
+                    }
+                })
+            })
+            .collect();
+        drop(senders);
+        drop(external_tx);
+        for (from, value) in external_rx {
+            self.external.push(Message {
+                from,
+                values: vec![value],
+            });
+        }
+        for handle in handles {
+            let machine = handle.join().expect("machine thread panicked")?;
+            let (mut machine, receiver, _) = machine.swap_io(VecDeque::new(), Vec::new());
+            machine.input.extend(receiver.0.try_iter());
+            self.machines.push(machine);
+        }
+        Ok(self)
+    }
+}
+
+/// The values a machine received, in order, according to a delivery log.
+/// Feeding them to a fresh copy of the machine reproduces its run.
+pub fn replay_inputs(log: &[Delivery], node: usize) -> Vec<i64> {
+    log.iter()
+        .filter(|d| d.to == node)
+        .map(|d| d.value)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::assemble;
+    use super::*;
+    fn amplifiers(program: &str, phases: &[i64], feedback: bool) -> Network {
+        let machine: Machine = program.parse().unwrap();
+        let mut network = Network::chain(vec![machine; phases.len()], feedback);
+        for (node, &phase) in phases.iter().enumerate() {
+            network.push_input(node, phase);
+        }
+        network.push_input(0, 0);
+        network
+    }
+    #[test]
+    fn amplifier_chain() {
+        let program = "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0";
+        let mut network = amplifiers(program, &[4, 3, 2, 1, 0], false);
+        assert_eq!(network.run().unwrap(), NetworkState::Halted);
+        let external = network.take_external();
+        assert_eq!(
+            external,
+            vec![Message {
+                from: 4,
+                values: vec![43210]
+            }]
+        );
+    }
+    #[test]
+    fn amplifier_feedback_loop() {
+        let program = "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,\
+                       1001,28,-1,28,1005,28,6,99,0,0,5";
+        let mut network = amplifiers(program, &[9, 8, 7, 6, 5], true);
+        assert_eq!(network.run().unwrap(), NetworkState::Halted);
+        let last = network.log().last().unwrap();
+        assert_eq!((last.from, last.to, last.value), (Some(4), 0, 139_629_729));
+
+        let mut threaded = amplifiers(program, &[9, 8, 7, 6, 5], true)
+            .run_threaded()
+            .unwrap();
+        assert!(threaded.take_external().is_empty());
+        let pending: Vec<_> = threaded.machines()[0].input().iter().copied().collect();
+        assert_eq!(pending, vec![139_629_729]);
+
+        let mut threaded = amplifiers(program, &[9, 8, 7, 6, 5], false)
+            .run_threaded()
+            .unwrap();
+        let mut sequential = amplifiers(program, &[9, 8, 7, 6, 5], false);
+        sequential.run().unwrap();
+        assert_eq!(threaded.take_external(), sequential.take_external());
+    }
+    #[test]
+    fn addressed_packets() {
+        let program = assemble(
+            "
+                    IN -> [a]
+                    ADD [a] 1 -> [to]
+                    MUL [a] 10 -> [y]
+                    OUT [to]
+                    OUT [a]
+                    OUT [y]
+                    IN -> [x]
+                    IN -> [x]
+                    HLT
+            a:      .data 0
+            to:     .data 0
+            x:      .data 0
+            y:      .data 0
+            ",
+        )
+        .unwrap();
+        let run = || {
+            let mut network = Network::new(vec![Machine::new(program.clone()); 3]);
+            for node in 0..3 {
+                network.set_route(node, Route::Addressed).unwrap();
+                network.push_input(node, node as i64);
+            }
+            assert_eq!(network.run().unwrap(), NetworkState::Idle);
+            network
+        };
+        let mut network = run();
+        let states: Vec<_> = network.machines().iter().map(Machine::state).collect();
+        assert_eq!(
+            states,
+            vec![State::WaitingForInput, State::Halted, State::Halted]
+        );
+        assert_eq!(
+            network.take_external(),
+            vec![Message {
+                from: 2,
+                values: vec![3, 2, 20]
+            }]
+        );
+        assert_eq!(network.log(), run().log());
+        let inputs = replay_inputs(network.log(), 2);
+        assert_eq!(inputs, vec![2, 1, 10]);
+        let mut replay = Machine::new(program);
+        inputs.into_iter().for_each(|v| replay.push_input(v));
+        assert_eq!(replay.run().unwrap(), State::Halted);
+        assert_eq!(replay.take_output(), vec![3, 2, 20]);
+    }
+    #[test]
+    fn idle_input() {
+        let mut network = Network::new(vec!["3,0,4,0,99".parse().unwrap()]);
+        network.set_idle_input(Some(-1));
+        network.run().unwrap();
+        assert_eq!(network.take_external()[0].values, vec![-1]);
+    }
+    #[test]
+    fn unknown_nodes() {
+        let mut network = Network::new(vec!["3,0,4,0,99".parse().unwrap(); 2]);
+        assert!(matches!(
+            network.set_route(0, Route::To(2)),
+            Err(MyError::UnknownNode(2))
+        ));
+        assert!(matches!(
+            network.set_route(2, Route::External),
+            Err(MyError::UnknownNode(2))
+        ));
+        network.set_route(0, Route::To(1)).unwrap();
+        network.push_input(0, 5);
+        assert_eq!(network.run().unwrap(), NetworkState::Halted);
+        assert_eq!(network.take_external()[0].values, vec![5]);
+    }
+}
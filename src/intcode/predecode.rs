@@ -0,0 +1,215 @@
+use super::{
+    op_at, to_address, Input, Machine, Memory, Mode, MyError, Operation, Output, Param, State,
+};
+
+/// The widest instruction, which bounds how far back a write can change one.
+const MAX_WIDTH: usize = 4;
+
+/// A parameter with its mode resolved at decode time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operand {
+    Address(usize),
+    Immediate(i64),
+    Relative(i64),
+}
+
+impl Operand {
+    /// Returns `None` for parameters that always fault, leaving the error to `step`.
+    fn new(param: Param) -> Option<Self> {
+        Some(match param.mode {
+            Mode::Position => Operand::Address(to_address(param.value).ok()?),
+            Mode::Immediate => Operand::Immediate(param.value),
+            Mode::Relative => Operand::Relative(param.value),
+        })
+    }
+    fn address(self, relative_base: i64) -> Option<usize> {
+        match self {
+            Operand::Address(address) => Some(address),
+            Operand::Immediate(_) => None,
+            Operand::Relative(offset) => to_address(relative_base.checked_add(offset)?).ok(),
+        }
+    }
+    fn load(self, memory: &Memory, relative_base: i64) -> Option<i64> {
+        match self {
+            Operand::Immediate(value) => Some(value),
+            _ => memory.read(self.address(relative_base)?).ok(),
+        }
+    }
+}
+
+/// A compact, copyable form of an `Operation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    Add(Operand, Operand, Operand),
+    Mul(Operand, Operand, Operand),
+    LessThan(Operand, Operand, Operand),
+    Equals(Operand, Operand, Operand),
+    JumpIfTrue(Operand, Operand),
+    JumpIfFalse(Operand, Operand),
+    AdjustRelativeBase(Operand),
+    /// Halt, I/O and invalid instructions are left to `Machine::step`.
+    Step {
+        width: usize,
+    },
+}
+
+impl Instruction {
+    fn compile(memory: &[i64], ip: usize) -> Self {
+        let op = match op_at(memory, ip) {
+            Ok(op) => op,
+            Err(_) => return Instruction::Step { width: 1 },
+        };
+        let width = op.width();
+        let binary = |src1, src2, dest| {
+            Some((
+                Operand::new(src1)?,
+                Operand::new(src2)?,
+                Operand::new(dest)?,
+            ))
+        };
+        let jump = |cond, target| Some((Operand::new(cond)?, Operand::new(target)?));
+        let compiled = match op {
+            Operation::Add(o) => {
+                binary(o.src1, o.src2, o.dest).map(|(a, b, c)| Instruction::Add(a, b, c))
+            }
+            Operation::Mul(o) => {
+                binary(o.src1, o.src2, o.dest).map(|(a, b, c)| Instruction::Mul(a, b, c))
+            }
+            Operation::LessThan(o) => {
+                binary(o.src1, o.src2, o.dest).map(|(a, b, c)| Instruction::LessThan(a, b, c))
+            }
+            Operation::Equals(o) => {
+                binary(o.src1, o.src2, o.dest).map(|(a, b, c)| Instruction::Equals(a, b, c))
+            }
+            Operation::JumpIfTrue(o) => {
+                jump(o.cond, o.target).map(|(c, t)| Instruction::JumpIfTrue(c, t))
+            }
+            Operation::JumpIfFalse(o) => {
+                jump(o.cond, o.target).map(|(c, t)| Instruction::JumpIfFalse(c, t))
+            }
+            Operation::AdjustRelativeBase(o) => {
+                Operand::new(o.offset).map(Instruction::AdjustRelativeBase)
+            }
+            _ => None,
+        };
+        compiled.unwrap_or(Instruction::Step { width })
+    }
+    fn width(self) -> usize {
+        match self {
+            Instruction::Add(..)
+            | Instruction::Mul(..)
+            | Instruction::LessThan(..)
+            | Instruction::Equals(..) => 4,
+            Instruction::JumpIfTrue(..) | Instruction::JumpIfFalse(..) => 3,
+            Instruction::AdjustRelativeBase(_) => 2,
+            Instruction::Step { width } => width,
+        }
+    }
+}
+
+/// Compiled instructions by address, filled in as they are first executed.
+///
+/// A write drops every cached instruction that covers the written cell, so
+/// self-modifying programs behave exactly as without the cache.
+#[derive(Debug, Clone, Default)]
+pub(super) struct DecodeCache {
+    instructions: Vec<Option<Instruction>>,
+    /// Cells covered by some cached instruction, so most writes skip the search.
+    code: Vec<bool>,
+}
+
+impl DecodeCache {
+    fn get(&mut self, memory: &[i64], ip: usize) -> Instruction {
+        if let Some(Some(instruction)) = self.instructions.get(ip) {
+            return *instruction;
+        }
+        let instruction = Instruction::compile(memory, ip);
+        if ip >= memory.len() {
+            // past the image there is nothing worth caching, and a runaway
+            // jump can land anywhere
+            return instruction;
+        }
+        if ip >= self.instructions.len() {
+            self.instructions.resize(ip + 1, None);
+        }
+        self.instructions[ip] = Some(instruction);
+        let end = ip + instruction.width();
+        if end > self.code.len() {
+            self.code.resize(end, false);
+        }
+        self.code[ip..end].iter_mut().for_each(|c| *c = true);
+        instruction
+    }
+    pub fn invalidate(&mut self, address: usize) {
+        if !self.code.get(address).copied().unwrap_or(false) {
+            return;
+        }
+        let start = address.saturating_sub(MAX_WIDTH - 1);
+        let end = (address + 1).min(self.instructions.len());
+        for ip in start..end {
+            if let Some(instruction) = self.instructions[ip] {
+                if ip + instruction.width() > address {
+                    self.instructions[ip] = None;
+                }
+            }
+        }
+    }
+    /// Executes the instruction at `ip` from the cache. Returns `None` without
+    /// changing anything if it has to go through `Machine::step` instead,
+    /// which is the case for halt, I/O and any instruction that would fail.
+    fn execute(&mut self, memory: &mut Memory, ip: &mut usize, rb: &mut i64) -> Option<()> {
+        let instruction = self.get(memory, *ip);
+        let load = |operand: Operand| operand.load(memory, *rb);
+        let next = *ip + instruction.width();
+        let (dest, value) = match instruction {
+            Instruction::Add(a, b, dest) => (dest, load(a)?.checked_add(load(b)?)?),
+            Instruction::Mul(a, b, dest) => (dest, load(a)?.checked_mul(load(b)?)?),
+            Instruction::LessThan(a, b, dest) => (dest, (load(a)? < load(b)?) as i64),
+            Instruction::Equals(a, b, dest) => (dest, (load(a)? == load(b)?) as i64),
+            Instruction::JumpIfTrue(cond, target) | Instruction::JumpIfFalse(cond, target) => {
+                let jump = (load(cond)? != 0) == matches!(instruction, Instruction::JumpIfTrue(..));
+                *ip = if jump {
+                    to_address(load(target)?).ok()?
+                } else {
+                    next
+                };
+                return Some(());
+            }
+            Instruction::AdjustRelativeBase(offset) => {
+                *rb = rb.checked_add(load(offset)?)?;
+                *ip = next;
+                return Some(());
+            }
+            Instruction::Step { .. } => return None,
+        };
+        let address = dest.address(*rb)?;
+        memory.write(address, value).ok()?;
+        self.invalidate(address);
+        *ip = next;
+        Some(())
+    }
+    /// Executes cached instructions until one has to go through `Machine::step`.
+    fn run(&mut self, memory: &mut Memory, ip: &mut usize, rb: &mut i64) {
+        while self.execute(memory, ip, rb).is_some() {}
+    }
+    pub fn clear(&mut self) {
+        self.instructions.clear();
+        self.code.clear();
+    }
+}
+
+impl<I: Input, O: Output> Machine<I, O> {
+    /// Like `run`, but executes cached instructions directly where possible.
+    pub(super) fn run_predecoded(&mut self) -> Result<State, MyError> {
+        loop {
+            if let (State::Running, None) = (self.state, &self.trace) {
+                if let Some(decoded) = self.decoded.as_mut() {
+                    decoded.run(&mut self.memory, &mut self.ip, &mut self.relative_base);
+                }
+            }
+            if self.step()? != State::Running {
+                return Ok(self.state);
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use super::{op_at, param_at, Machine, Mode, Operation, State};
+use rayon::prelude::*;
+use std::convert::TryFrom;
+use std::ops::RangeInclusive;
+
+/// A memory cell to vary during a search and the values to try for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchCell {
+    pub address: usize,
+    pub range: RangeInclusive<i64>,
+}
+
+impl SearchCell {
+    pub fn new(address: usize, range: RangeInclusive<i64>) -> Self {
+        Self { address, range }
+    }
+    /// The number of values to try, or `None` if it doesn't fit in a `u64`.
+    fn len(&self) -> Option<u64> {
+        if self.range.is_empty() {
+            return Some(0);
+        }
+        let len = i128::from(*self.range.end()) - i128::from(*self.range.start()) + 1;
+        u64::try_from(len).ok()
+    }
+}
+
+/// The number of combinations of cell values, or `None` if it doesn't fit in
+/// a `u64`.
+pub fn search_space(cells: &[SearchCell]) -> Option<u64> {
+    let lens: Vec<_> = cells.iter().map(SearchCell::len).collect();
+    if lens.contains(&Some(0)) {
+        return Some(0);
+    }
+    lens.into_iter()
+        .try_fold(1u64, |total, len| total.checked_mul(len?))
+}
+
+/// Maps a position in the search space to one value per cell,
+/// with the last cell varying fastest. The search space must fit in a `u64`.
+fn values_at(cells: &[SearchCell], mut index: u64) -> Vec<i64> {
+    let mut values = vec![0; cells.len()];
+    for (value, cell) in values.iter_mut().zip(cells).rev() {
+        let len = cell.len().expect("search space fits in a u64");
+        *value = (i128::from(*cell.range.start()) + i128::from(index % len)) as i64;
+        index /= len;
+    }
+    values
+}
+
+/// Whether the next instruction does I/O, can't be decoded, or touches one of
+/// the cells, either as part of the instruction or through a parameter.
+fn depends_on(machine: &Machine, cells: &[SearchCell]) -> bool {
+    let memory = machine.memory();
+    let ip = machine.ip();
+    let width = match op_at(memory, ip) {
+        Ok(Operation::Input(_)) | Ok(Operation::Output(_)) | Err(_) => return true,
+        Ok(op) => op.width(),
+    };
+    let touched = |address: usize| cells.iter().any(|c| c.address == address);
+    (ip..ip + width).any(touched)
+        || (1..width as u32).any(|n| match param_at(memory, ip, n) {
+            Ok(p) if p.mode == Mode::Immediate => false,
+            Ok(p) => machine.address(p).map_or(true, touched),
+            Err(_) => true,
+        })
+}
+
+/// The number of instructions a single attempt of a search may execute
+/// before it counts as never halting.
+pub const DEFAULT_MAX_STEPS: u64 = 1_000_000;
+
+/// Runs the machine up to the first instruction that depends on the cells,
+/// but at most `max_steps` instructions, and returns it with the steps left.
+/// Everything before it is the same for every combination of values, so
+/// the attempts of a search all fork from this checkpoint.
+pub(super) fn checkpoint(
+    machine: &Machine,
+    cells: &[SearchCell],
+    max_steps: u64,
+) -> (Machine, u64) {
+    let mut machine = machine.clone();
+    let mut steps_left = max_steps;
+    while steps_left > 0 && machine.state() == State::Running && !depends_on(&machine, cells) {
+        // without I/O a failing instruction leaves the machine unchanged
+        if machine.step().is_err() {
+            break;
+        }
+        steps_left -= 1;
+    }
+    (machine, steps_left)
+}
+
+/// Patches the cells of a copy of the machine and runs it for at most
+/// `max_steps` instructions. Returns the machine if it halted.
+pub(super) fn attempt(
+    machine: &Machine,
+    cells: &[SearchCell],
+    values: &[i64],
+    max_steps: u64,
+) -> Option<Machine> {
+    let mut machine = machine.clone();
+    for (cell, &value) in cells.iter().zip(values) {
+        machine.memory.write(cell.address, value).ok()?;
+    }
+    match machine.run_for(max_steps) {
+        Ok(State::Halted) => Some(machine),
+        _ => None,
+    }
+}
+
+/// Tries every combination of cell values in parallel and returns the first
+/// one (in search order) for which the patched program halts within
+/// `max_steps` instructions and `accept` holds for the halted machine.
+/// Combinations that fail to run or run out of steps are skipped.
+///
+/// The program is only run once up to the first instruction that depends on
+/// the cells; every combination continues from there. Nothing is found if
+/// there are more combinations than `search_space` can count.
+pub fn find_inputs<F>(
+    machine: &Machine,
+    cells: &[SearchCell],
+    max_steps: u64,
+    accept: F,
+) -> Option<Vec<i64>>
+where
+    F: Fn(&Machine) -> bool + Sync,
+{
+    let total = search_space(cells)?;
+    let (machine, steps_left) = checkpoint(machine, cells, max_steps);
+    (0..total)
+        .into_par_iter()
+        .find_first(|&index| {
+            attempt(&machine, cells, &values_at(cells, index), steps_left)
+                .is_some_and(|m| accept(&m))
+        })
+        .map(|index| values_at(cells, index))
+}
+
+/// Finds cell values for which the program leaves `target` at `address`.
+pub fn find_inputs_for_value(
+    machine: &Machine,
+    cells: &[SearchCell],
+    address: usize,
+    target: i64,
+    max_steps: u64,
+) -> Option<Vec<i64>> {
+    find_inputs(machine, cells, max_steps, |m| {
+        m.memory().get(address) == Some(&target)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn search_order() {
+        let cells = [SearchCell::new(1, 0..=2), SearchCell::new(2, 5..=6)];
+        let all: Vec<_> = (0..6).map(|i| values_at(&cells, i)).collect();
+        assert_eq!(all, vec![[0, 5], [0, 6], [1, 5], [1, 6], [2, 5], [2, 6]]);
+    }
+    #[test]
+    fn wide_ranges() {
+        let widest = SearchCell::new(1, i64::MIN..=i64::MAX);
+        assert_eq!(search_space(std::slice::from_ref(&widest)), None);
+        let wide = [SearchCell::new(1, i64::MIN..=i64::MAX - 1)];
+        assert_eq!(search_space(&wide), Some(u64::MAX));
+        assert_eq!(values_at(&wide, u64::MAX - 1), vec![i64::MAX - 1]);
+        assert_eq!(search_space(&[wide[0].clone(), wide[0].clone()]), None);
+        let empty = SearchCell::new(2, RangeInclusive::new(1, 0));
+        assert_eq!(search_space(&[widest.clone(), empty]), Some(0));
+        let machine: Machine = "1,0,0,0,99".parse().unwrap();
+        assert_eq!(
+            find_inputs(&machine, &[widest], DEFAULT_MAX_STEPS, |_| true),
+            None
+        );
+    }
+    #[test]
+    fn finds_noun_and_verb() {
+        // memory[0] = memory[noun] * memory[verb]
+        let machine: Machine = "2,0,0,0,99,3,4,5,6,7".parse().unwrap();
+        let cells = [SearchCell::new(1, 0..=9), SearchCell::new(2, 0..=9)];
+        assert_eq!(
+            find_inputs_for_value(&machine, &cells, 0, 42, DEFAULT_MAX_STEPS),
+            Some(vec![8, 9])
+        );
+        assert_eq!(
+            find_inputs_for_value(&machine, &cells, 0, 1000, DEFAULT_MAX_STEPS),
+            None
+        );
+    }
+    #[test]
+    fn forks_from_checkpoint() {
+        // memory[13] = 6 * 7, then memory[0] = memory[13] + memory[12]
+        let machine: Machine = "1102,6,7,13,1,13,12,0,99,0,0,0,0,0".parse().unwrap();
+        let cells = [SearchCell::new(12, 0..=9)];
+        let (forked, steps_left) = checkpoint(&machine, &cells, 10);
+        assert_eq!(steps_left, 9);
+        assert_eq!(forked.ip(), 4);
+        assert_eq!(forked.memory()[13], 42);
+        assert_eq!(
+            find_inputs_for_value(&machine, &cells, 0, 45, DEFAULT_MAX_STEPS),
+            Some(vec![3])
+        );
+    }
+    #[test]
+    fn step_limit() {
+        // loops forever unless memory[9] is set, then memory[0] = 1
+        let machine: Machine = "1006,9,0,1101,1,0,0,99,0,0".parse().unwrap();
+        let cells = [SearchCell::new(9, 0..=1)];
+        assert_eq!(
+            find_inputs_for_value(&machine, &cells, 0, 1, 1000),
+            Some(vec![1])
+        );
+        // the program loops before it ever reads the cell
+        let machine: Machine = "1105,1,0,99,0".parse().unwrap();
+        let cells = [SearchCell::new(4, 0..=1)];
+        assert_eq!(find_inputs(&machine, &cells, 1000, |_| true), None);
+    }
+}
@@ -0,0 +1,236 @@
+use super::{Machine, Memory, State, DEFAULT_LIMIT};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("line {line}: unknown field `{field}`")]
+    UnknownField { line: usize, field: String },
+    #[error("line {line}: invalid value `{value}`")]
+    InvalidValue { line: usize, value: String },
+    #[error("missing field `{0}`")]
+    MissingField(&'static str),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// The complete state of a `Machine` with the default queue I/O.
+///
+/// Snapshots are saved as text with one `field value` pair per line, where
+/// lists of values are comma separated like Intcode programs:
+///
+/// ```text
+/// state waiting
+/// ip 0
+/// relative_base 0
+/// input
+/// output 6
+/// memory 3,11,1001,11,1,11,4,11,1105,1,0,6
+/// ```
+///
+/// An optional `limit` line holds the memory limit, a number or `none`, and
+/// is left out for `DEFAULT_LIMIT`. Tracing and the
+/// predecode cache are not part of a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub memory: Vec<i64>,
+    pub limit: Option<usize>,
+    pub ip: usize,
+    pub relative_base: i64,
+    pub state: State,
+    /// Input that was queued but not read yet.
+    pub input: Vec<i64>,
+    /// Output that was produced but not taken yet.
+    pub output: Vec<i64>,
+}
+
+impl Snapshot {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SnapshotError> {
+        fs::read_to_string(path)?.parse()
+    }
+}
+
+impl Machine {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            memory: self.memory.to_vec(),
+            limit: self.memory.limit(),
+            ip: self.ip,
+            relative_base: self.relative_base,
+            state: self.state,
+            input: self.input.iter().copied().collect(),
+            output: self.output.clone(),
+        }
+    }
+}
+
+impl From<Snapshot> for Machine {
+    fn from(snapshot: Snapshot) -> Self {
+        let mut memory = Memory::new(snapshot.memory);
+        memory.set_limit(snapshot.limit);
+        let mut machine = Machine::with_io(Vec::new(), snapshot.input.into(), snapshot.output);
+        machine.memory = memory;
+        machine.ip = snapshot.ip;
+        machine.relative_base = snapshot.relative_base;
+        machine.state = snapshot.state;
+        machine
+    }
+}
+
+fn state_name(state: State) -> &'static str {
+    match state {
+        State::Running => "running",
+        State::WaitingForInput => "waiting",
+        State::Halted => "halted",
+    }
+}
+
+fn join(values: &[i64]) -> String {
+    let values: Vec<_> = values.iter().map(i64::to_string).collect();
+    values.join(",")
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "state {}", state_name(self.state))?;
+        writeln!(f, "ip {}", self.ip)?;
+        writeln!(f, "relative_base {}", self.relative_base)?;
+        match self.limit {
+            Some(DEFAULT_LIMIT) => {}
+            Some(limit) => writeln!(f, "limit {}", limit)?,
+            None => writeln!(f, "limit none")?,
+        }
+        writeln!(f, "input {}", join(&self.input))?;
+        writeln!(f, "output {}", join(&self.output))?;
+        writeln!(f, "memory {}", join(&self.memory))
+    }
+}
+
+impl FromStr for Snapshot {
+    type Err = SnapshotError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut memory = None;
+        let mut limit = Some(DEFAULT_LIMIT);
+        let mut ip = None;
+        let mut relative_base = None;
+        let mut state = None;
+        let mut input = None;
+        let mut output = None;
+        for (i, line) in s.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let mut parts = line.trim().splitn(2, ' ');
+            let field = parts.next().unwrap_or_default();
+            let value = parts.next().unwrap_or_default().trim();
+            let invalid = || SnapshotError::InvalidValue {
+                line: i + 1,
+                value: value.to_string(),
+            };
+            let list = || -> Result<Vec<i64>, SnapshotError> {
+                if value.is_empty() {
+                    return Ok(Vec::new());
+                }
+                value
+                    .split(',')
+                    .map(|v| v.trim().parse().map_err(|_| invalid()))
+                    .collect()
+            };
+            match field {
+                "memory" => memory = Some(list()?),
+                "input" => input = Some(list()?),
+                "output" => output = Some(list()?),
+                "limit" if value == "none" => limit = None,
+                "limit" => limit = Some(value.parse().map_err(|_| invalid())?),
+                "ip" => ip = Some(value.parse().map_err(|_| invalid())?),
+                "relative_base" => relative_base = Some(value.parse().map_err(|_| invalid())?),
+                "state" => {
+                    state = Some(match value {
+                        "running" => State::Running,
+                        "waiting" => State::WaitingForInput,
+                        "halted" => State::Halted,
+                        _ => return Err(invalid()),
+                    })
+                }
+                _ => {
+                    return Err(SnapshotError::UnknownField {
+                        line: i + 1,
+                        field: field.to_string(),
+                    })
+                }
+            }
+        }
+        Ok(Snapshot {
+            memory: memory.ok_or(SnapshotError::MissingField("memory"))?,
+            limit,
+            ip: ip.ok_or(SnapshotError::MissingField("ip"))?,
+            relative_base: relative_base.ok_or(SnapshotError::MissingField("relative_base"))?,
+            state: state.ok_or(SnapshotError::MissingField("state"))?,
+            input: input.unwrap_or_default(),
+            output: output.unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn round_trip() {
+        let mut machine: Machine = "3,11,1001,11,1,11,4,11,1105,1,0,0".parse().unwrap();
+        machine.set_memory_limit(Some(100));
+        machine.push_input(5);
+        machine.run().unwrap();
+        machine.push_input(7);
+        let snapshot = machine.snapshot();
+        let text = snapshot.to_string();
+        assert_eq!(
+            text,
+            "state waiting\nip 0\nrelative_base 0\nlimit 100\ninput 7\noutput 6\n\
+             memory 3,11,1001,11,1,11,4,11,1105,1,0,6\n"
+        );
+        let path = std::env::temp_dir().join("intcode-snapshot-round-trip");
+        snapshot.save(&path).unwrap();
+        let loaded = Snapshot::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, snapshot);
+
+        let mut restored = Machine::from(loaded);
+        assert_eq!(restored.run().unwrap(), machine.run().unwrap());
+        assert_eq!(restored.take_output(), machine.take_output());
+        assert_eq!(restored.snapshot(), machine.snapshot());
+
+        let mut machine: Machine = "99".parse().unwrap();
+        assert_eq!(
+            machine.snapshot().to_string(),
+            "state running\nip 0\nrelative_base 0\ninput \noutput \nmemory 99\n"
+        );
+        machine.set_memory_limit(None);
+        let text = machine.snapshot().to_string();
+        assert!(text.contains("limit none\n"));
+        assert_eq!(text.parse::<Snapshot>().unwrap(), machine.snapshot());
+    }
+    #[test]
+    fn invalid_snapshots() {
+        let errors = [
+            (
+                "ip 0\nrelative_base 0\nstate running\n",
+                "missing field `memory`",
+            ),
+            ("ip x\n", "line 1: invalid value `x`"),
+            (
+                "memory 1\n\nstate paused\n",
+                "line 3: invalid value `paused`",
+            ),
+            ("registers 1\n", "line 1: unknown field `registers`"),
+        ];
+        for &(text, message) in &errors {
+            let error = text.parse::<Snapshot>().unwrap_err();
+            assert_eq!(error.to_string(), message);
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use super::search::{attempt, checkpoint};
+use super::{find_inputs_for_value, Machine, SearchCell};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SymbolicError {
+    #[error("instruction `{opcode}` at address {ip} can't be evaluated symbolically")]
+    Unsupported { ip: usize, opcode: i64 },
+    #[error("opcode at address {0} depends on the variables")]
+    SymbolicOpcode(usize),
+    #[error("instruction at address {0} uses an address that depends on the variables")]
+    SymbolicAddress(usize),
+    #[error("result depends on memory read through a variable address")]
+    UnknownResult,
+    #[error("arithmetic overflow")]
+    Overflow,
+}
+
+/// A polynomial with integer coefficients in a fixed number of variables.
+/// Terms map exponent vectors to their non-zero coefficients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    vars: usize,
+    terms: BTreeMap<Vec<u32>, i64>,
+}
+
+impl Polynomial {
+    pub fn constant(vars: usize, c: i64) -> Self {
+        let mut terms = BTreeMap::new();
+        if c != 0 {
+            terms.insert(vec![0; vars], c);
+        }
+        Self { vars, terms }
+    }
+    pub fn variable(vars: usize, var: usize) -> Self {
+        let mut exponents = vec![0; vars];
+        exponents[var] = 1;
+        let mut terms = BTreeMap::new();
+        terms.insert(exponents, 1);
+        Self { vars, terms }
+    }
+    pub fn as_constant(&self) -> Option<i64> {
+        match self.terms.len() {
+            0 => Some(0),
+            1 => self.terms.get(&vec![0; self.vars]).copied(),
+            _ => None,
+        }
+    }
+    fn coefficient(&self, exponents: &[u32]) -> i64 {
+        self.terms.get(exponents).copied().unwrap_or(0)
+    }
+    pub fn degree_in(&self, var: usize) -> u32 {
+        self.terms.keys().map(|e| e[var]).max().unwrap_or(0)
+    }
+    fn insert_term(&mut self, exponents: Vec<u32>, c: i64) -> Option<()> {
+        let sum = self.coefficient(&exponents).checked_add(c)?;
+        if sum == 0 {
+            self.terms.remove(&exponents);
+        } else {
+            self.terms.insert(exponents, sum);
+        }
+        Some(())
+    }
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let mut result = self.clone();
+        for (exponents, &c) in &other.terms {
+            result.insert_term(exponents.clone(), c)?;
+        }
+        Some(result)
+    }
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let mut result = Self::constant(self.vars, 0);
+        for (e1, &c1) in &self.terms {
+            for (e2, &c2) in &other.terms {
+                let exponents = e1.iter().zip(e2).map(|(a, b)| a + b).collect();
+                result.insert_term(exponents, c1.checked_mul(c2)?)?;
+            }
+        }
+        Some(result)
+    }
+    /// Replaces a variable by a value.
+    pub fn substitute(&self, var: usize, value: i64) -> Option<Self> {
+        let mut result = Self::constant(self.vars, 0);
+        for (exponents, &c) in &self.terms {
+            let factor = value.checked_pow(exponents[var])?;
+            let mut exponents = exponents.clone();
+            exponents[var] = 0;
+            result.insert_term(exponents, c.checked_mul(factor)?)?;
+        }
+        Some(result)
+    }
+}
+
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.terms.is_empty() {
+            return write!(f, "0");
+        }
+        for (i, (exponents, &c)) in self.terms.iter().rev().enumerate() {
+            let sign = if c < 0 { "-" } else { "+" };
+            match i {
+                0 if c < 0 => write!(f, "-")?,
+                0 => {}
+                _ => write!(f, " {} ", sign)?,
+            }
+            let factors: Vec<String> = exponents
+                .iter()
+                .enumerate()
+                .filter(|&(_, &e)| e > 0)
+                .map(|(var, &e)| match e {
+                    1 => format!("x{}", var),
+                    _ => format!("x{}^{}", var, e),
+                })
+                .collect();
+            match (c.abs(), factors.is_empty()) {
+                (c, true) => write!(f, "{}", c)?,
+                (1, false) => write!(f, "{}", factors.join("*"))?,
+                (c, false) => write!(f, "{}*{}", c, factors.join("*"))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs a straight-line program of `Add`, `Mul` and `Halt` instructions over
+/// polynomials, treating the cells at `variables` as unknowns `x0, x1, ...`,
+/// and returns the final content of the cell at `result`.
+///
+/// Values read through an address that depends on a variable are unknown;
+/// this is fine as long as they never reach the result, an opcode or an address.
+/// Cells are kept by address, so writes far beyond the program cost nothing.
+pub fn evaluate(
+    memory: &[i64],
+    variables: &[usize],
+    result: usize,
+) -> Result<Polynomial, SymbolicError> {
+    let vars = variables.len();
+    let mut cells: BTreeMap<usize, Option<Polynomial>> = memory
+        .iter()
+        .enumerate()
+        .map(|(address, &c)| (address, Some(Polynomial::constant(vars, c))))
+        .collect();
+    for (var, &address) in variables.iter().enumerate() {
+        cells.insert(address, Some(Polynomial::variable(vars, var)));
+    }
+    let zero = Some(Polynomial::constant(vars, 0));
+    let constant_at = |cells: &BTreeMap<usize, Option<Polynomial>>, address: usize| {
+        cells
+            .get(&address)
+            .unwrap_or(&zero)
+            .as_ref()
+            .and_then(Polynomial::as_constant)
+    };
+    let mut ip = 0;
+    loop {
+        let opcode = constant_at(&cells, ip).ok_or(SymbolicError::SymbolicOpcode(ip))?;
+        if opcode == 99 {
+            break;
+        }
+        if !matches!(opcode % 100, 1 | 2) || !(0..10000).contains(&opcode) {
+            return Err(SymbolicError::Unsupported { ip, opcode });
+        }
+        let mut operands = Vec::with_capacity(2);
+        for n in 1..=2 {
+            let immediate = match opcode / 10_i64.pow(n + 1) % 10 {
+                0 => false,
+                1 => true,
+                _ => return Err(SymbolicError::Unsupported { ip, opcode }),
+            };
+            let param = ip + n as usize;
+            let operand = if immediate {
+                cells.get(&param).unwrap_or(&zero).clone()
+            } else {
+                match constant_at(&cells, param) {
+                    Some(a) if a >= 0 => cells.get(&(a as usize)).unwrap_or(&zero).clone(),
+                    Some(_) => return Err(SymbolicError::Unsupported { ip, opcode }),
+                    None => None,
+                }
+            };
+            operands.push(operand);
+        }
+        let dest = constant_at(&cells, ip + 3).ok_or(SymbolicError::SymbolicAddress(ip))?;
+        if dest < 0 {
+            return Err(SymbolicError::Unsupported { ip, opcode });
+        }
+        let value = match (&operands[0], &operands[1]) {
+            (Some(a), Some(b)) if opcode % 100 == 1 => {
+                Some(a.checked_add(b).ok_or(SymbolicError::Overflow)?)
+            }
+            (Some(a), Some(b)) => Some(a.checked_mul(b).ok_or(SymbolicError::Overflow)?),
+            _ => None,
+        };
+        cells.insert(dest as usize, value);
+        ip += 4;
+    }
+    cells
+        .get(&result)
+        .unwrap_or(&zero)
+        .clone()
+        .ok_or(SymbolicError::UnknownResult)
+}
+
+/// Enumerates all but the last variable and solves for the last one directly
+/// whenever it appears at most linearly. Each candidate is confirmed by `verify`.
+fn solve_polynomial<F>(
+    p: &Polynomial,
+    ranges: &[RangeInclusive<i64>],
+    fixed: &mut Vec<i64>,
+    target: i64,
+    verify: &F,
+) -> Option<Vec<i64>>
+where
+    F: Fn(&[i64]) -> bool,
+{
+    let var = fixed.len();
+    if var == ranges.len() {
+        return if p.as_constant() == Some(target) && verify(fixed) {
+            Some(fixed.clone())
+        } else {
+            None
+        };
+    }
+    let range = ranges[var].clone();
+    if var + 1 == ranges.len() && p.degree_in(var) == 1 {
+        // p = a * x + b
+        let mut linear = vec![0; p.vars];
+        linear[var] = 1;
+        let a = p.coefficient(&linear);
+        let b = p.coefficient(&vec![0; p.vars]);
+        let x = target.checked_sub(b)?;
+        let value = x.checked_div(a)?;
+        if x.checked_rem(a)? != 0 || !range.contains(&value) {
+            return None;
+        }
+        fixed.push(value);
+        let solution = solve_polynomial(&p.substitute(var, value)?, ranges, fixed, target, verify);
+        fixed.pop();
+        return solution;
+    }
+    for value in range {
+        if let Some(sub) = p.substitute(var, value) {
+            fixed.push(value);
+            let solution = solve_polynomial(&sub, ranges, fixed, target, verify);
+            fixed.pop();
+            if solution.is_some() {
+                return solution;
+            }
+        }
+    }
+    None
+}
+
+/// Like `find_inputs_for_value`, but first tries to express the result
+/// symbolically and solve for the target directly. Falls back to brute force
+/// if the program can't be evaluated symbolically. Either way, a solution
+/// must halt within `max_steps` instructions when run.
+pub fn solve(
+    machine: &Machine,
+    cells: &[SearchCell],
+    address: usize,
+    target: i64,
+    max_steps: u64,
+) -> Option<Vec<i64>> {
+    let variables: Vec<_> = cells.iter().map(|c| c.address).collect();
+    let p = match evaluate(machine.memory(), &variables, address) {
+        Ok(p) => p,
+        Err(_) => return find_inputs_for_value(machine, cells, address, target, max_steps),
+    };
+    let ranges: Vec<_> = cells.iter().map(|c| c.range.clone()).collect();
+    let (machine, steps_left) = checkpoint(machine, cells, max_steps);
+    let verify = |values: &[i64]| {
+        attempt(&machine, cells, values, steps_left)
+            .is_some_and(|m| m.memory().get(address) == Some(&target))
+    };
+    solve_polynomial(&p, &ranges, &mut Vec::new(), target, &verify)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::DEFAULT_MAX_STEPS;
+    use super::*;
+    use std::fs;
+    #[test]
+    fn polynomial_arithmetic() {
+        let x = Polynomial::variable(2, 0);
+        let y = Polynomial::variable(2, 1);
+        let three = Polynomial::constant(2, 3);
+        let p = x.checked_add(&three).unwrap().checked_mul(&y).unwrap();
+        let p = p.checked_mul(&x).unwrap();
+        assert_eq!(p.to_string(), "x0^2*x1 + 3*x0*x1");
+        assert_eq!(p.degree_in(0), 2);
+        assert_eq!(p.substitute(0, 2).unwrap().to_string(), "10*x1");
+        let q = p.substitute(0, 1).unwrap().substitute(1, -2).unwrap();
+        assert_eq!(q.as_constant(), Some(-8));
+    }
+    #[test]
+    fn day_2() {
+        let machine: Machine = fs::read_to_string("input/02").unwrap().parse().unwrap();
+        let p = evaluate(machine.memory(), &[1, 2], 0).unwrap();
+        assert_eq!(p.degree_in(0), 1);
+        assert_eq!(p.degree_in(1), 1);
+        let cells = [SearchCell::new(1, 0..=99), SearchCell::new(2, 0..=99)];
+        for &target in &[3_516_593, 19_690_720] {
+            assert_eq!(
+                solve(&machine, &cells, 0, target, DEFAULT_MAX_STEPS),
+                find_inputs_for_value(&machine, &cells, 0, target, DEFAULT_MAX_STEPS)
+            );
+        }
+    }
+    #[test]
+    fn falls_back_to_brute_force() {
+        // adds noun and verb, but jumps over a halt first
+        let machine: Machine = "1105,1,4,99,1,0,0,0,99".parse().unwrap();
+        assert_eq!(
+            evaluate(machine.memory(), &[5, 6], 0),
+            Err(SymbolicError::Unsupported {
+                ip: 0,
+                opcode: 1105
+            })
+        );
+        let cells = [SearchCell::new(5, 0..=8), SearchCell::new(6, 0..=8)];
+        assert_eq!(
+            solve(&machine, &cells, 0, 1, DEFAULT_MAX_STEPS),
+            Some(vec![1, 7])
+        );
+        // loops forever unless memory[9] is set
+        let machine: Machine = "1006,9,0,1101,1,0,0,99,0,0".parse().unwrap();
+        let cells = [SearchCell::new(9, 0..=1)];
+        assert_eq!(solve(&machine, &cells, 0, 1, 1000), Some(vec![1]));
+    }
+    #[test]
+    fn overflowing_division() {
+        // memory[0] = -x0, which can't be i64::MIN
+        let machine = Machine::new(vec![2, 5, 6, 0, 99, 0, -1]);
+        let cells = [SearchCell::new(5, 0..=10)];
+        assert_eq!(
+            solve(&machine, &cells, 0, i64::MIN, DEFAULT_MAX_STEPS),
+            None
+        );
+        assert_eq!(
+            solve(&machine, &cells, 0, -7, DEFAULT_MAX_STEPS),
+            Some(vec![7])
+        );
+    }
+    #[test]
+    fn distant_writes() {
+        let far = 1_000_000_000_000;
+        let memory = [1, 5, 5, far as i64, 99, 0];
+        let p = evaluate(&memory, &[5], far).unwrap();
+        assert_eq!(p.to_string(), "2*x0");
+        let mut machine = Machine::new(memory.to_vec());
+        machine.set_memory_limit(Some(100));
+        let cells = [SearchCell::new(5, 0..=9)];
+        assert_eq!(solve(&machine, &cells, far, 4, DEFAULT_MAX_STEPS), None);
+    }
+}
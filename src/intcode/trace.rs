@@ -0,0 +1,44 @@
+use super::{Operation, Param};
+use std::fmt;
+
+impl Operation {
+    /// The parameters whose values the instruction reads, in operand order.
+    pub fn reads(&self) -> Vec<Param> {
+        match self {
+            Self::Halt(_) | Self::Input(_) => vec![],
+            Self::Add(op) => vec![op.src1, op.src2],
+            Self::Mul(op) => vec![op.src1, op.src2],
+            Self::LessThan(op) => vec![op.src1, op.src2],
+            Self::Equals(op) => vec![op.src1, op.src2],
+            Self::Output(op) => vec![op.src],
+            Self::JumpIfTrue(op) => vec![op.cond, op.target],
+            Self::JumpIfFalse(op) => vec![op.cond, op.target],
+            Self::AdjustRelativeBase(op) => vec![op.offset],
+        }
+    }
+}
+
+/// A record of one executed instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub ip: usize,
+    pub op: Operation,
+    /// Resolved values of the parameters in `Operation::reads`.
+    pub reads: Vec<i64>,
+    /// Memory cells written by the instruction as `(address, value)`.
+    pub writes: Vec<(usize, i64)>,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:>5}: {}", self.ip, self.op)?;
+        if !self.reads.is_empty() {
+            let reads: Vec<_> = self.reads.iter().map(i64::to_string).collect();
+            write!(f, "  ; reads {}", reads.join(" "))?;
+        }
+        for (address, value) in &self.writes {
+            write!(f, "  ; [{}] <- {}", address, value)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,4 @@
+pub mod intcode;
+pub mod orbit;
+pub mod password;
+pub mod wire;
@@ -0,0 +1,33 @@
+use adventofcode::orbit::OrbitMap;
+use anyhow::Result as AnyResult;
+use std::env;
+use std::time::Instant;
+
+/// A single chain `COM)B1`, `B1)B2`, ..., as deep as a map of n bodies gets.
+fn chain(n: usize) -> String {
+    let mut map = String::from("COM)B1\n");
+    for i in 2..n {
+        map.push_str(&format!("B{})B{}\n", i - 1, i));
+    }
+    map
+}
+
+fn main() -> AnyResult<()> {
+    let n = env::args().nth(1).map_or(Ok(20_000), |s| s.parse())?;
+    let map: OrbitMap = chain(n).parse()?;
+    let start = Instant::now();
+    let walked: usize = map.bodies().map(|b| map.depth(b)).sum();
+    let walking = start.elapsed();
+    let start = Instant::now();
+    let memoized = map.total_orbits();
+    let topological = start.elapsed();
+    assert_eq!(walked, memoized);
+    println!("Total orbits: {}", memoized);
+    println!("Walking ancestors: {:?}", walking);
+    println!("Memoized depths:   {:?}", topological);
+    println!(
+        "Speedup: {:.2}x",
+        walking.as_secs_f64() / topological.as_secs_f64()
+    );
+    Ok(())
+}
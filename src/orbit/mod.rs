@@ -0,0 +1,259 @@
+pub use self::validate::{validate, Diagnostic};
+use daggy::petgraph::algo;
+use daggy::petgraph::visit::Walker;
+use daggy::{Dag, NodeIndex};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::rc::Rc;
+use std::str::FromStr;
+use thiserror::Error;
+
+mod validate;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum MyError {
+    #[error("invalid orbit syntax `{0}`")]
+    InvalidOrbitSyntax(String),
+    #[error("`{0}` orbits more than one central")]
+    SeveralCentrals(String),
+    #[error("orbits close the cycle `{}`", cycle(.0))]
+    Cycle(Vec<String>),
+    #[error("line {line}: {source}")]
+    AtLine { line: usize, source: Box<MyError> },
+}
+
+/// Writes bodies that orbit each other in turn like `A)B)C)A`.
+fn cycle(bodies: &[String]) -> String {
+    let mut cycle = bodies.join(")");
+    if let Some(first) = bodies.first() {
+        cycle.push(')');
+        cycle.push_str(first);
+    }
+    cycle
+}
+
+/// One line of a map: `central)trabant`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Orbit<'a> {
+    pub central: &'a str,
+    pub trabant: &'a str,
+}
+
+impl<'a> TryFrom<&'a str> for Orbit<'a> {
+    type Error = MyError;
+    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
+        let mut parts = s.split(')');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(central), Some(trabant), None) if !central.is_empty() && !trabant.is_empty() => {
+                Ok(Orbit { central, trabant })
+            }
+            _ => Err(MyError::InvalidOrbitSyntax(s.to_owned())),
+        }
+    }
+}
+
+/// A body in an `OrbitMap`, only meaningful for the map it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Body(NodeIndex);
+
+/// The depth of every body of a map, computed once for all of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Depths(Vec<usize>);
+
+impl Depths {
+    /// The number of bodies this one orbits directly or indirectly.
+    pub fn get(&self, body: Body) -> usize {
+        self.0[body.0.index()]
+    }
+    /// The number of direct and indirect orbits of all bodies.
+    pub fn total(&self) -> usize {
+        self.0.iter().sum()
+    }
+}
+
+/// The bodies of a map and what they orbit, with every name stored once and
+/// shared between the graph and the lookup by name.
+///
+/// Edges point from a central to its trabants, so every body has at most one
+/// parent.
+#[derive(Debug, Default)]
+pub struct OrbitMap {
+    dag: Dag<Rc<str>, ()>,
+    bodies: HashMap<Rc<str>, Body>,
+}
+
+impl OrbitMap {
+    fn intern(&mut self, name: &str) -> Body {
+        if let Some(&body) = self.bodies.get(name) {
+            return body;
+        }
+        let name: Rc<str> = name.into();
+        let body = Body(self.dag.add_node(Rc::clone(&name)));
+        self.bodies.insert(name, body);
+        body
+    }
+    /// Adds an orbit, or leaves the map unchanged if it is an error.
+    pub fn add_orbit(&mut self, orbit: Orbit) -> Result<(), MyError> {
+        let known_trabant = self.body(orbit.trabant);
+        if known_trabant.is_some_and(|t| self.central(t).is_some()) {
+            return Err(MyError::SeveralCentrals(orbit.trabant.to_owned()));
+        }
+        if orbit.central == orbit.trabant {
+            return Err(MyError::Cycle(vec![orbit.central.to_owned()]));
+        }
+        // a new body can't close a cycle, so if adding the edge fails both
+        // bodies were known before
+        let central = self.intern(orbit.central);
+        let trabant = self.intern(orbit.trabant);
+        if self.dag.add_edge(central.0, trabant.0, ()).is_err() {
+            // the trabant already orbits the central, directly or not
+            let mut bodies: Vec<_> = std::iter::once(central)
+                .chain(self.ancestors(central).take_while(|&b| b != trabant))
+                .chain(Some(trabant))
+                .map(|b| self.name(b).to_owned())
+                .collect();
+            bodies.reverse();
+            return Err(MyError::Cycle(bodies));
+        }
+        Ok(())
+    }
+    pub fn len(&self) -> usize {
+        self.bodies.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bodies.is_empty()
+    }
+    pub fn body(&self, name: &str) -> Option<Body> {
+        self.bodies.get(name).copied()
+    }
+    pub fn name(&self, body: Body) -> &str {
+        &self.dag[body.0]
+    }
+    pub fn bodies(&self) -> impl Iterator<Item = Body> + '_ {
+        self.dag.graph().node_indices().map(Body)
+    }
+    /// The body this one orbits directly.
+    pub fn central(&self, body: Body) -> Option<Body> {
+        self.dag
+            .parents(body.0)
+            .walk_next(&self.dag)
+            .map(|(_, n)| Body(n))
+    }
+    /// The bodies this one orbits directly or indirectly, nearest first.
+    pub fn ancestors(&self, body: Body) -> impl Iterator<Item = Body> + '_ {
+        std::iter::successors(self.central(body), move |&b| self.central(b))
+    }
+    /// The number of bodies this one orbits directly or indirectly, found
+    /// by walking to the root. Use `depths` to ask for many bodies.
+    pub fn depth(&self, body: Body) -> usize {
+        self.ancestors(body).count()
+    }
+    /// The depths of all bodies, each derived from its central's in
+    /// topological order, which takes linear time even for deep maps.
+    pub fn depths(&self) -> Depths {
+        let order = algo::toposort(self.dag.graph(), None).expect("orbit maps have no cycles");
+        let mut depths = vec![0; self.dag.node_count()];
+        for n in order {
+            if let Some(central) = self.central(Body(n)) {
+                depths[n.index()] = depths[central.0.index()] + 1;
+            }
+        }
+        Depths(depths)
+    }
+    /// The number of direct and indirect orbits of all bodies, the map's
+    /// checksum.
+    pub fn total_orbits(&self) -> usize {
+        self.depths().total()
+    }
+    /// The nearest body that both bodies are or orbit.
+    pub fn common_ancestor(&self, a: Body, b: Body) -> Option<Body> {
+        let (depth_a, depth_b) = (self.depth(a), self.depth(b));
+        let chain = |body, skip| std::iter::once(body).chain(self.ancestors(body)).skip(skip);
+        let a_chain = chain(a, depth_a.saturating_sub(depth_b));
+        let b_chain = chain(b, depth_b.saturating_sub(depth_a));
+        a_chain.zip(b_chain).find(|(a, b)| a == b).map(|(a, _)| a)
+    }
+    /// The number of orbits between the two bodies, or `None` if they are
+    /// in different maps.
+    pub fn distance(&self, a: Body, b: Body) -> Option<usize> {
+        let common = self.depth(self.common_ancestor(a, b)?);
+        Some(self.depth(a) + self.depth(b) - 2 * common)
+    }
+    /// The number of orbital transfers needed to bring the first body into
+    /// orbit around the same central as the second.
+    pub fn transfers(&self, from: Body, to: Body) -> Option<usize> {
+        self.distance(self.central(from)?, self.central(to)?)
+    }
+}
+
+impl FromStr for OrbitMap {
+    type Err = MyError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut map = Self::default();
+        for (i, line) in s.lines().enumerate() {
+            Orbit::try_from(line)
+                .and_then(|orbit| map.add_orbit(orbit))
+                .map_err(|e| MyError::AtLine {
+                    line: i + 1,
+                    source: Box::new(e),
+                })?;
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const EXAMPLE: &str = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L";
+    #[test]
+    fn examples() {
+        let mut map: OrbitMap = EXAMPLE.parse().unwrap();
+        assert_eq!(map.len(), 12);
+        assert_eq!(map.total_orbits(), 42);
+        let body = |name| map.body(name).unwrap();
+        assert_eq!(map.depth(body("COM")), 0);
+        assert_eq!(map.depth(body("L")), 7);
+        let depths = map.depths();
+        assert!(map.bodies().all(|b| depths.get(b) == map.depth(b)));
+        let common = map.common_ancestor(body("H"), body("I")).unwrap();
+        assert_eq!(map.name(common), "B");
+        assert_eq!(map.common_ancestor(body("L"), body("E")), Some(body("E")));
+        assert_eq!(map.distance(body("L"), body("E")), Some(3));
+        assert_eq!(map.transfers(body("L"), body("E")), Some(3));
+
+        map.add_orbit(Orbit::try_from("K)YOU").unwrap()).unwrap();
+        map.add_orbit(Orbit::try_from("I)SAN").unwrap()).unwrap();
+        let (you, san) = (map.body("YOU").unwrap(), map.body("SAN").unwrap());
+        assert_eq!(map.transfers(you, san), Some(4));
+        assert_eq!(map.transfers(you, you), Some(0));
+    }
+    #[test]
+    fn invalid_maps() {
+        let error = |s: &str| s.parse::<OrbitMap>().unwrap_err().to_string();
+        assert_eq!(error("COM)B\nB"), "line 2: invalid orbit syntax `B`");
+        assert_eq!(error("COM)B)C"), "line 1: invalid orbit syntax `COM)B)C`");
+        assert_eq!(error("COM)"), "line 1: invalid orbit syntax `COM)`");
+        assert_eq!(
+            error("COM)B\nA)B"),
+            "line 2: `B` orbits more than one central"
+        );
+        assert_eq!(
+            error("COM)A\nB)C\nC)D\nD)B"),
+            "line 4: orbits close the cycle `B)C)D)B`"
+        );
+        assert_eq!(error("A)A"), "line 1: orbits close the cycle `A)A`");
+        let mut map: OrbitMap = "A)B\nC)D".parse().unwrap();
+        let (b, d) = (map.body("B").unwrap(), map.body("D").unwrap());
+        assert_eq!(map.common_ancestor(b, d), None);
+        assert_eq!(map.transfers(b, d), None);
+        // failed orbits leave no bodies behind
+        let orbit = |s| Orbit::try_from(s).unwrap();
+        assert!(map.add_orbit(orbit("X)B")).is_err());
+        assert!(map.add_orbit(orbit("Y)Y")).is_err());
+        assert!(map.add_orbit(orbit("B)A")).is_err());
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.body("X"), None);
+        assert_eq!(map.body("Y"), None);
+    }
+}
@@ -0,0 +1,208 @@
+use super::{cycle, Orbit};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// A problem with a map that keeps it from being a single tree around
+/// `COM`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    MalformedLine {
+        line: usize,
+        text: String,
+    },
+    /// A body with several centrals, each with the line it is given on.
+    SeveralCentrals {
+        trabant: String,
+        centrals: Vec<(String, usize)>,
+    },
+    MissingCom,
+    /// `COM` given as orbiting `central` on `line`.
+    ComOrbits {
+        central: String,
+        line: usize,
+    },
+    /// Bodies that orbit each other in turn.
+    Cycle(Vec<String>),
+    /// Bodies that orbit `root` and not `COM`, including the root.
+    Unreachable {
+        root: String,
+        bodies: Vec<String>,
+    },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MalformedLine { line, text } => {
+                write!(f, "line {}: invalid orbit syntax `{}`", line, text)
+            }
+            Self::SeveralCentrals { trabant, centrals } => {
+                let centrals: Vec<_> = centrals
+                    .iter()
+                    .map(|(central, line)| format!("`{}` on line {}", central, line))
+                    .collect();
+                write!(f, "`{}` orbits {}", trabant, centrals.join(" and "))
+            }
+            Self::MissingCom => write!(f, "no body is called `COM`"),
+            Self::ComOrbits { central, line } => {
+                write!(f, "`COM` orbits `{}` on line {}", central, line)
+            }
+            Self::Cycle(bodies) => write!(f, "the cycle `{}` can't reach `COM`", cycle(bodies)),
+            Self::Unreachable { root, bodies } if bodies.len() == 1 => {
+                write!(f, "`{}` can't reach `COM`", root)
+            }
+            Self::Unreachable { root, bodies } => {
+                let bodies: Vec<_> = bodies.iter().map(|b| format!("`{}`", b)).collect();
+                write!(
+                    f,
+                    "{} around `{}` can't reach `COM`",
+                    bodies.join(", "),
+                    root
+                )
+            }
+        }
+    }
+}
+
+/// Where following the centrals of a body ends.
+#[derive(Clone, Copy)]
+enum End<'a> {
+    Root(&'a str),
+    Cycle,
+}
+
+/// Finds every problem of a map at once, unlike parsing an `OrbitMap`, which
+/// stops at the first one.
+///
+/// Bodies with several centrals are followed through the first one given.
+/// Bodies in a cycle, or orbiting one, are only reported as that cycle. `COM`
+/// is always taken as the root, so the centrals it is given are reported on
+/// their own.
+pub fn validate(map: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut centrals: BTreeMap<&str, Vec<(&str, usize)>> = BTreeMap::new();
+    let mut bodies = BTreeSet::new();
+    for (i, line) in map.lines().enumerate() {
+        match Orbit::try_from(line) {
+            Ok(orbit) => {
+                bodies.insert(orbit.central);
+                bodies.insert(orbit.trabant);
+                centrals
+                    .entry(orbit.trabant)
+                    .or_default()
+                    .push((orbit.central, i + 1));
+            }
+            Err(_) => diagnostics.push(Diagnostic::MalformedLine {
+                line: i + 1,
+                text: line.to_owned(),
+            }),
+        }
+    }
+    for (trabant, centrals) in &centrals {
+        if centrals.len() > 1 {
+            diagnostics.push(Diagnostic::SeveralCentrals {
+                trabant: (*trabant).to_owned(),
+                centrals: centrals.iter().map(|&(c, l)| (c.to_owned(), l)).collect(),
+            });
+        }
+    }
+    if !bodies.contains("COM") {
+        diagnostics.push(Diagnostic::MissingCom);
+    }
+    for &(central, line) in centrals.get("COM").into_iter().flatten() {
+        diagnostics.push(Diagnostic::ComOrbits {
+            central: central.to_owned(),
+            line,
+        });
+    }
+
+    // `COM` is a root even when it is given a central
+    let central = |body| match body {
+        "COM" => None,
+        _ => centrals.get(body).map(|c| c[0].0),
+    };
+    let mut ends: BTreeMap<&str, End> = BTreeMap::new();
+    let mut unreachable: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for &body in &bodies {
+        // follow the centrals until a body whose end is known, a root, or a
+        // body seen before on this path, which closes a cycle
+        let mut path = vec![body];
+        let mut on_path: HashMap<&str, usize> = HashMap::new();
+        on_path.insert(body, 0);
+        let end = loop {
+            let last = path[path.len() - 1];
+            if let Some(&end) = ends.get(last) {
+                break end;
+            }
+            match central(last) {
+                None => break End::Root(last),
+                Some(next) => match on_path.get(next) {
+                    Some(&start) => {
+                        let mut bodies: Vec<_> = path[start..].iter().rev().collect();
+                        let first = (0..bodies.len()).min_by_key(|&i| bodies[i]).unwrap();
+                        bodies.rotate_left(first);
+                        let bodies = bodies.into_iter().map(|b| (*b).to_owned()).collect();
+                        diagnostics.push(Diagnostic::Cycle(bodies));
+                        break End::Cycle;
+                    }
+                    None => {
+                        on_path.insert(next, path.len());
+                        path.push(next);
+                    }
+                },
+            }
+        };
+        for &b in &path {
+            if ends.insert(b, end).is_none() {
+                if let End::Root(root) = end {
+                    if root != "COM" {
+                        unreachable.entry(root).or_default().push(b.to_owned());
+                    }
+                }
+            }
+        }
+    }
+    if bodies.contains("COM") {
+        for (root, mut bodies) in unreachable {
+            bodies.sort();
+            diagnostics.push(Diagnostic::Unreachable {
+                root: root.to_owned(),
+                bodies,
+            });
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn diagnostics() {
+        assert_eq!(validate("COM)B\nB)C\nCOM)D"), vec![]);
+        let map = "COM)B\nB)C\nB\nA)C\nX)Y\nY)Z\nP)Q\nQ)R\nR)P\nR)S\nCOM)B)C";
+        let messages: Vec<_> = validate(map).iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "line 3: invalid orbit syntax `B`",
+                "line 11: invalid orbit syntax `COM)B)C`",
+                "`C` orbits `B` on line 2 and `A` on line 4",
+                "the cycle `P)Q)R)P` can't reach `COM`",
+                "`A` can't reach `COM`",
+                "`X`, `Y`, `Z` around `X` can't reach `COM`",
+            ]
+        );
+        let messages: Vec<_> = validate("X)COM\nCOM)A")
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            vec!["`COM` orbits `X` on line 1", "`X` can't reach `COM`"]
+        );
+        assert_eq!(validate("A)B")[0], Diagnostic::MissingCom,);
+        assert_eq!(validate("A)B").len(), 1);
+    }
+}
@@ -0,0 +1,136 @@
+use super::{MyError, NonDecreasing, Password, Rule};
+use std::ops::RangeInclusive;
+
+/// How numbers are written as passwords: the radix of the digits and the
+/// length they are padded to with leading zeros.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Format {
+    radix: u32,
+    length: usize,
+}
+
+impl Default for Format {
+    /// Decimal without padding.
+    fn default() -> Self {
+        Self {
+            radix: 10,
+            length: 1,
+        }
+    }
+}
+
+impl Format {
+    /// Digits go up to `z` in radix 36. A `length` of 0 or 1 means no
+    /// padding.
+    pub fn new(radix: u32, length: usize) -> Result<Self, MyError> {
+        if !(2..=36).contains(&radix) {
+            return Err(MyError::InvalidRadix(radix));
+        }
+        Ok(Self {
+            radix,
+            length: length.max(1),
+        })
+    }
+    pub fn radix(&self) -> u32 {
+        self.radix
+    }
+    pub fn length(&self) -> usize {
+        self.length
+    }
+    pub fn password(&self, mut n: usize) -> Password {
+        let radix = self.radix as usize;
+        let mut digits = Vec::new();
+        while n > 0 || digits.len() < self.length {
+            digits.push((n % radix) as u8);
+            n /= radix;
+        }
+        digits.reverse();
+        Password { digits }
+    }
+    /// The number written by the digits, or `None` if it doesn't fit.
+    pub fn value(&self, digits: &[u8]) -> Option<usize> {
+        digits.iter().try_fold(0usize, |n, &d| {
+            n.checked_mul(self.radix as usize)?
+                .checked_add(usize::from(d))
+        })
+    }
+    /// Reads a password written in this radix, like `00ff`.
+    pub fn parse(&self, s: &str) -> Result<Password, MyError> {
+        let digits = s
+            .chars()
+            .map(|c| c.to_digit(self.radix).map(|d| d as u8))
+            .collect::<Option<Vec<_>>>()
+            .filter(|digits| !digits.is_empty())
+            .ok_or_else(|| MyError::InvalidNumber {
+                number: s.to_owned(),
+                radix: self.radix,
+            })?;
+        Ok(Password { digits })
+    }
+    /// Parses a range of candidates written as `low-high` in this radix,
+    /// both included.
+    pub fn parse_range(&self, s: &str) -> Result<RangeInclusive<usize>, MyError> {
+        let invalid = || MyError::InvalidRange(s.to_owned());
+        let i = s.find('-').ok_or_else(invalid)?;
+        let bound = |s| {
+            usize::from_str_radix(s, self.radix).map_err(|_| MyError::InvalidNumber {
+                number: s.to_owned(),
+                radix: self.radix,
+            })
+        };
+        Ok(bound(&s[..i])?..=bound(&s[i + 1..])?)
+    }
+    /// The passwords in the range that satisfy the rule, in increasing order.
+    ///
+    /// When the rule only allows digits that never decrease, just those
+    /// candidates are generated. Otherwise every number is checked.
+    pub fn passwords<'a>(
+        &'a self,
+        range: RangeInclusive<usize>,
+        rule: &'a dyn Rule,
+    ) -> Box<dyn Iterator<Item = Password> + 'a> {
+        if rule.implies_nondecreasing() {
+            Box::new(NonDecreasing::new(*self, range).filter(move |p| p.meets(rule)))
+        } else {
+            Box::new(self.each(range, rule))
+        }
+    }
+    /// The passwords in the range that satisfy the rule, found by checking
+    /// every number.
+    pub fn each<'a>(
+        &'a self,
+        range: RangeInclusive<usize>,
+        rule: &'a dyn Rule,
+    ) -> impl Iterator<Item = Password> + 'a {
+        range
+            .map(move |n| self.password(n))
+            .filter(move |p| p.meets(rule))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn radix_and_length() {
+        let hex = Format::new(16, 4).unwrap();
+        assert_eq!(hex.password(255).digits, vec![0, 0, 15, 15]);
+        assert_eq!(hex.password(0x12345).digits, vec![1, 2, 3, 4, 5]);
+        assert_eq!(hex.parse("00fF").unwrap(), hex.password(255));
+        assert_eq!(hex.value(&[0, 0, 15, 15]), Some(255));
+        assert_eq!(hex.parse_range("0a-ff"), Ok(10..=255));
+        assert_eq!(hex.password(255).to_string(), "00ff");
+        let base36 = Format::new(36, 0).unwrap();
+        assert_eq!(base36.password(0).to_string(), "0");
+        assert_eq!(base36.password(36 * 36 - 1).to_string(), "zz");
+        assert_eq!(Format::new(37, 6), Err(MyError::InvalidRadix(37)));
+        assert_eq!(
+            Format::new(2, 8)
+                .unwrap()
+                .parse("102")
+                .unwrap_err()
+                .to_string(),
+            "invalid number `102` in radix 2"
+        );
+    }
+}
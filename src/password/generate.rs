@@ -0,0 +1,90 @@
+use super::{Format, Password};
+use std::ops::RangeInclusive;
+
+/// The numbers in a range whose digits never decrease, in increasing order.
+///
+/// Only these candidates are visited, so a range of six digit numbers takes
+/// a few thousand steps instead of a million.
+#[derive(Debug, Clone)]
+pub struct NonDecreasing {
+    format: Format,
+    digits: Vec<u8>,
+    high: usize,
+}
+
+impl NonDecreasing {
+    pub fn new(format: Format, range: RangeInclusive<usize>) -> Self {
+        // the smallest candidate at or above the start keeps the digits up
+        // to the first decrease and repeats the one before it
+        let mut digits = format.password(*range.start()).digits;
+        if let Some(i) = digits.windows(2).position(|w| w[0] > w[1]) {
+            let d = digits[i];
+            digits[i + 1..].iter_mut().for_each(|x| *x = d);
+        }
+        Self {
+            format,
+            digits,
+            high: *range.end(),
+        }
+    }
+}
+
+impl Iterator for NonDecreasing {
+    type Item = Password;
+    fn next(&mut self) -> Option<Password> {
+        if self
+            .format
+            .value(&self.digits)
+            .is_none_or(|n| n > self.high)
+        {
+            return None;
+        }
+        let current = self.digits.clone();
+        let max = (self.format.radix() - 1) as u8;
+        match self.digits.iter().rposition(|&d| d < max) {
+            Some(i) => {
+                let d = self.digits[i] + 1;
+                self.digits[i..].iter_mut().for_each(|x| *x = d);
+            }
+            None => self.digits = vec![1; self.digits.len() + 1],
+        }
+        Some(Password { digits: current })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn candidates() {
+        let decimal = Format::default();
+        let values = |range| -> Vec<_> {
+            NonDecreasing::new(decimal, range)
+                .map(|p| decimal.value(&p.digits).unwrap())
+                .collect()
+        };
+        assert_eq!(values(0..=12), vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 11, 12]);
+        assert_eq!(values(97..=115), vec![99, 111, 112, 113, 114, 115]);
+        assert_eq!(
+            values(210..=234),
+            vec![222, 223, 224, 225, 226, 227, 228, 229, 233, 234]
+        );
+        assert_eq!(values(RangeInclusive::new(5, 4)), vec![]);
+        let all = (0..=100_000)
+            .filter(|&n| {
+                Password::from_usize(n)
+                    .digits
+                    .windows(2)
+                    .all(|w| w[0] <= w[1])
+            })
+            .count();
+        assert_eq!(NonDecreasing::new(decimal, 0..=100_000).count(), all);
+        let codes: Vec<_> = NonDecreasing::new(Format::new(3, 3).unwrap(), 0..=26)
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(
+            codes,
+            vec!["000", "001", "002", "011", "012", "022", "111", "112", "122", "222"]
+        );
+    }
+}
@@ -0,0 +1,123 @@
+mod format;
+mod generate;
+mod rule;
+
+pub use self::format::Format;
+pub use self::generate::NonDecreasing;
+pub use self::rule::{
+    parse_rule, parse_rules, runs, All, Any, DigitSum, Forbidden, Length, Monotonic, Not, Rule, Run,
+};
+use std::fmt;
+use std::ops::RangeInclusive;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum MyError {
+    #[error("unknown rule `{0}`")]
+    UnknownRule(String),
+    #[error("invalid rule `{0}`")]
+    InvalidRule(String),
+    #[error("unbalanced parentheses in `{0}`")]
+    UnbalancedParentheses(String),
+    #[error("invalid range `{0}`, expected `low-high`")]
+    InvalidRange(String),
+    #[error("invalid number `{number}` in radix {radix}")]
+    InvalidNumber { number: String, radix: u32 },
+    #[error("radix {0} is not between 2 and 36")]
+    InvalidRadix(u32),
+}
+
+/// The rules of the first part of day 4.
+pub const PART_ONE: [&str; 3] = ["length=6", "nondecreasing", "run=2.."];
+/// The rules of the second part of day 4.
+pub const PART_TWO: [&str; 3] = ["length=6", "nondecreasing", "run=2"];
+
+/// The digits of a password, most significant first, each below the radix
+/// of its `Format`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Password {
+    pub digits: Vec<u8>,
+}
+
+impl Password {
+    pub fn from_usize(u: usize) -> Self {
+        Format::default().password(u)
+    }
+    pub fn meets(&self, rule: &dyn Rule) -> bool {
+        rule.check(&self.digits)
+    }
+}
+
+impl fmt::Display for Password {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &d in &self.digits {
+            let c = std::char::from_digit(u32::from(d), 36).ok_or(fmt::Error)?;
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a range of decimal candidates written as `low-high`, both included.
+pub fn parse_range(s: &str) -> Result<RangeInclusive<usize>, MyError> {
+    Format::default().parse_range(s)
+}
+
+/// Counts the decimal numbers in the range whose digits satisfy the rule,
+/// using `Format::passwords`.
+pub fn count(range: RangeInclusive<usize>, rule: &dyn Rule) -> usize {
+    Format::default().passwords(range, rule).count()
+}
+
+/// Counts the decimal numbers in the range whose digits satisfy the rule by
+/// checking every one of them.
+pub fn count_each(range: RangeInclusive<usize>, rule: &dyn Rule) -> usize {
+    Format::default().each(range, rule).count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn examples() {
+        let part_one = parse_rules(PART_ONE.iter().copied()).unwrap();
+        let part_two = parse_rules(PART_TWO.iter().copied()).unwrap();
+        let check = |rules: &All, n| Password::from_usize(n).meets(rules);
+        assert!(check(&part_one, 111_111));
+        assert!(!check(&part_one, 223_450));
+        assert!(!check(&part_one, 123_789));
+        assert!(check(&part_two, 112_233));
+        assert!(!check(&part_two, 123_444));
+        assert!(check(&part_two, 111_122));
+        assert_eq!(parse_range("10-20"), Ok(10..=20));
+        assert!(parse_range("10..20").is_err());
+    }
+    #[test]
+    fn counting_matches_checking_each() {
+        let rules = [
+            "length=6 nondecreasing run=2..",
+            "length=6 nondecreasing run=2",
+            "increasing forbid=5",
+            "any(nondecreasing) sum=10..20",
+            "any(nondecreasing,length=2)",
+            "not(decreasing)",
+        ];
+        let formats = [(10, 1), (16, 4), (36, 0), (2, 12)];
+        for rule in &rules {
+            let rule = parse_rules(rule.split(' ')).unwrap();
+            for range in [0..=1_000, 273_025..=300_000, 999_000..=1_001_000]
+                .iter()
+                .cloned()
+            {
+                assert_eq!(count(range.clone(), &rule), count_each(range, &rule));
+            }
+            for &(radix, length) in &formats {
+                let format = Format::new(radix, length).unwrap();
+                let range = 0..=20_000;
+                assert!(format
+                    .passwords(range.clone(), &rule)
+                    .eq(format.each(range, &rule)));
+            }
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use super::MyError;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// A condition on the digits of a password, most significant digit first.
+pub trait Rule: fmt::Debug + Sync {
+    fn check(&self, digits: &[u8]) -> bool;
+    /// Whether every password meeting the rule has digits that never
+    /// decrease, which allows counting without checking every number.
+    fn implies_nondecreasing(&self) -> bool {
+        false
+    }
+}
+
+/// The password has exactly this many digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Length(pub usize);
+
+impl Rule for Length {
+    fn check(&self, digits: &[u8]) -> bool {
+        digits.len() == self.0
+    }
+}
+
+/// Every digit compares the same way to the one before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Monotonic {
+    NonDecreasing,
+    Increasing,
+    NonIncreasing,
+    Decreasing,
+}
+
+impl Rule for Monotonic {
+    fn check(&self, digits: &[u8]) -> bool {
+        digits.windows(2).all(|w| match self {
+            Self::NonDecreasing => w[0] <= w[1],
+            Self::Increasing => w[0] < w[1],
+            Self::NonIncreasing => w[0] >= w[1],
+            Self::Decreasing => w[0] > w[1],
+        })
+    }
+    fn implies_nondecreasing(&self) -> bool {
+        match self {
+            Self::NonDecreasing | Self::Increasing => true,
+            Self::NonIncreasing | Self::Decreasing => false,
+        }
+    }
+}
+
+/// Some run of equal adjacent digits, taken as long as possible, has a
+/// length in the range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run(pub RangeInclusive<usize>);
+
+/// The lengths of the runs of equal adjacent digits.
+pub fn runs(digits: &[u8]) -> Vec<usize> {
+    let mut runs: Vec<usize> = Vec::new();
+    for (i, d) in digits.iter().enumerate() {
+        match runs.last_mut() {
+            Some(run) if i > 0 && digits[i - 1] == *d => *run += 1,
+            _ => runs.push(1),
+        }
+    }
+    runs
+}
+
+impl Rule for Run {
+    fn check(&self, digits: &[u8]) -> bool {
+        runs(digits).iter().any(|run| self.0.contains(run))
+    }
+}
+
+/// The sum of all digits lies in the range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigitSum(pub RangeInclusive<u32>);
+
+impl Rule for DigitSum {
+    fn check(&self, digits: &[u8]) -> bool {
+        self.0.contains(&digits.iter().map(|&d| u32::from(d)).sum())
+    }
+}
+
+/// None of the given digits occurs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forbidden(pub Vec<u8>);
+
+impl Rule for Forbidden {
+    fn check(&self, digits: &[u8]) -> bool {
+        !digits.iter().any(|d| self.0.contains(d))
+    }
+}
+
+#[derive(Debug)]
+pub struct All(pub Vec<Box<dyn Rule>>);
+
+impl Rule for All {
+    fn check(&self, digits: &[u8]) -> bool {
+        self.0.iter().all(|rule| rule.check(digits))
+    }
+    fn implies_nondecreasing(&self) -> bool {
+        self.0.iter().any(|rule| rule.implies_nondecreasing())
+    }
+}
+
+#[derive(Debug)]
+pub struct Any(pub Vec<Box<dyn Rule>>);
+
+impl Rule for Any {
+    fn check(&self, digits: &[u8]) -> bool {
+        self.0.iter().any(|rule| rule.check(digits))
+    }
+    fn implies_nondecreasing(&self) -> bool {
+        self.0.iter().all(|rule| rule.implies_nondecreasing())
+    }
+}
+
+#[derive(Debug)]
+pub struct Not(pub Box<dyn Rule>);
+
+impl Rule for Not {
+    fn check(&self, digits: &[u8]) -> bool {
+        !self.0.check(digits)
+    }
+}
+
+/// Splits at commas that are not nested in parentheses.
+fn split_top_level(s: &str) -> Result<Vec<&str>, MyError> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| MyError::UnbalancedParentheses(s.to_owned()))?
+            }
+            ',' if depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err(MyError::UnbalancedParentheses(s.to_owned()));
+    }
+    parts.push(&s[start..]);
+    Ok(parts)
+}
+
+/// Parses `n`, `a..b` (inclusive), `a..` or `..b` into a range.
+fn parse_range<T: std::str::FromStr + Copy + Default>(
+    s: &str,
+    max: T,
+) -> Option<RangeInclusive<T>> {
+    match s.find("..") {
+        Some(i) => {
+            let min = match &s[..i] {
+                "" => T::default(),
+                start => start.parse().ok()?,
+            };
+            let max = match &s[i + 2..] {
+                "" => max,
+                end => end.parse().ok()?,
+            };
+            Some(min..=max)
+        }
+        None => s.parse().ok().map(|n| n..=n),
+    }
+}
+
+/// Parses a rule written as one of
+///
+/// - `length=N`
+/// - `nondecreasing`, `increasing`, `nonincreasing` or `decreasing`
+/// - `run=R`: some run of equal digits has a length in `R`
+/// - `sum=R`: the digit sum is in `R`
+/// - `forbid=DIGITS`: none of the digits occurs, e.g. `forbid=07` or, in
+///   radix 16, `forbid=af`
+/// - `all(RULE,...)`, `any(RULE,...)` or `not(RULE)`
+///
+/// where a range `R` is written `N`, `A..B` (both included), `A..` or `..B`.
+pub fn parse_rule(s: &str) -> Result<Box<dyn Rule>, MyError> {
+    let s = s.trim();
+    let invalid = || MyError::InvalidRule(s.to_owned());
+    if let Some(open) = s.find('(') {
+        let inner = s[open + 1..].strip_suffix(')').ok_or_else(invalid)?;
+        let rules = split_top_level(inner)?
+            .into_iter()
+            .map(parse_rule)
+            .collect::<Result<Vec<_>, _>>()?;
+        return match &s[..open] {
+            "all" => Ok(Box::new(All(rules))),
+            "any" => Ok(Box::new(Any(rules))),
+            "not" if rules.len() == 1 => Ok(Box::new(Not(rules.into_iter().next().unwrap()))),
+            _ => Err(invalid()),
+        };
+    }
+    let (name, arg) = match s.find('=') {
+        Some(i) => (&s[..i], Some(&s[i + 1..])),
+        None => (s, None),
+    };
+    Ok(match (name, arg) {
+        ("length", Some(n)) => Box::new(Length(n.parse().map_err(|_| invalid())?)),
+        ("nondecreasing", None) => Box::new(Monotonic::NonDecreasing),
+        ("increasing", None) => Box::new(Monotonic::Increasing),
+        ("nonincreasing", None) => Box::new(Monotonic::NonIncreasing),
+        ("decreasing", None) => Box::new(Monotonic::Decreasing),
+        ("run", Some(r)) => Box::new(Run(parse_range(r, usize::MAX).ok_or_else(invalid)?)),
+        ("sum", Some(r)) => Box::new(DigitSum(parse_range(r, u32::MAX).ok_or_else(invalid)?)),
+        ("forbid", Some(digits)) => Box::new(Forbidden(
+            digits
+                .chars()
+                .map(|c| c.to_digit(36).map(|d| d as u8))
+                .collect::<Option<_>>()
+                .ok_or_else(invalid)?,
+        )),
+        _ => return Err(MyError::UnknownRule(s.to_owned())),
+    })
+}
+
+/// Parses several rules that must all hold.
+pub fn parse_rules<'a, I: IntoIterator<Item = &'a str>>(rules: I) -> Result<All, MyError> {
+    Ok(All(rules
+        .into_iter()
+        .map(parse_rule)
+        .collect::<Result<_, _>>()?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn built_in_rules() {
+        assert_eq!(runs(&[1, 1, 2, 2, 2, 3]), vec![2, 3, 1]);
+        assert!(Run(2..=2).check(&[1, 1, 2, 2, 2, 3]));
+        assert!(!Run(2..=2).check(&[1, 2, 3, 4, 4, 4]));
+        assert!(Monotonic::NonDecreasing.check(&[1, 1, 2]));
+        assert!(!Monotonic::Increasing.check(&[1, 1, 2]));
+        assert!(DigitSum(3..=4).check(&[1, 1, 2]));
+        assert!(!Forbidden(vec![2]).check(&[1, 1, 2]));
+        assert!(parse_rule("forbid=a").unwrap().check(&[9, 11]));
+    }
+    #[test]
+    fn parsing() {
+        assert!(parse_rule("run=..").unwrap().check(&[1]));
+        let rule = parse_rule("all(length=3,any(run=3..,sum=0..12),not(forbid=9))").unwrap();
+        assert!(rule.check(&[9, 9, 9]));
+        assert!(rule.check(&[1, 2, 9]));
+        assert!(!rule.check(&[1, 2, 3]));
+        assert!(!rule.check(&[9, 9, 9, 9]));
+        let errors: Vec<_> = [
+            "length",
+            "all(length=6",
+            "odd",
+            "not(length=1,length=2)",
+            "sum=1..x",
+        ]
+        .iter()
+        .map(|s| parse_rule(s).unwrap_err().to_string())
+        .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "unknown rule `length`",
+                "invalid rule `all(length=6`",
+                "unknown rule `odd`",
+                "invalid rule `not(length=1,length=2)`",
+                "invalid rule `sum=1..x`",
+            ]
+        );
+    }
+}
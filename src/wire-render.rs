@@ -0,0 +1,29 @@
+use adventofcode::wire::{junctions, render_ascii, render_svg, Wire};
+use anyhow::Result as AnyResult;
+use std::env;
+use std::fs;
+
+/// Usage: wire-render [input] [svg output]
+///
+/// Draws the wires that could be parsed to the terminal, marking where they
+/// cross, and reports the lines that could not be parsed.
+fn main() -> AnyResult<()> {
+    let mut args = env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| "input/03".to_owned());
+    let mut wires = Vec::new();
+    for (i, line) in fs::read_to_string(path)?.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match line.parse::<Wire>() {
+            Ok(wire) => wires.push(wire),
+            Err(e) => eprintln!("line {}: {}", i + 1, e),
+        }
+    }
+    let highlights: Vec<_> = junctions(&wires, 2).iter().map(|j| j.point).collect();
+    print!("{}", render_ascii(&wires, &highlights, 100, 50));
+    if let Some(svg) = args.next() {
+        fs::write(svg, render_svg(&wires, &highlights))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use super::{MyError, Vec2D, Vec3D, Vector};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// The directions a wire can take on a grid, and how they are written.
+///
+/// Every direction must be a unit step, with each component of the vector
+/// being `-1`, `0` or `1`.
+pub trait Alphabet: Copy + Eq + fmt::Debug {
+    type Vector: Vector;
+    /// Parses the direction at the start of a movement, like `U` or `NE`.
+    fn parse(token: &str) -> Result<Self, MyError>;
+    fn unit(self) -> Self::Vector;
+}
+
+fn single_char(token: &str) -> Result<char, MyError> {
+    let mut chars = token.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(MyError::UnknownDirection(token.to_owned())),
+    }
+}
+
+/// `U`, `D`, `L` and `R` on a plane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl TryFrom<char> for Direction {
+    type Error = MyError;
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'U' => Ok(Self::Up),
+            'D' => Ok(Self::Down),
+            'L' => Ok(Self::Left),
+            'R' => Ok(Self::Right),
+            _ => Err(MyError::UnknownDirection(c.to_string())),
+        }
+    }
+}
+
+impl Alphabet for Direction {
+    type Vector = Vec2D;
+    fn parse(token: &str) -> Result<Self, MyError> {
+        Self::try_from(single_char(token)?)
+    }
+    fn unit(self) -> Vec2D {
+        match self {
+            Self::Up => Vec2D::new(0, 1),
+            Self::Down => Vec2D::new(0, -1),
+            Self::Left => Vec2D::new(-1, 0),
+            Self::Right => Vec2D::new(1, 0),
+        }
+    }
+}
+
+/// The eight compass directions `N`, `NE`, `E`, ..., `NW` on a plane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compass {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Alphabet for Compass {
+    type Vector = Vec2D;
+    fn parse(token: &str) -> Result<Self, MyError> {
+        match token {
+            "N" => Ok(Self::North),
+            "NE" => Ok(Self::NorthEast),
+            "E" => Ok(Self::East),
+            "SE" => Ok(Self::SouthEast),
+            "S" => Ok(Self::South),
+            "SW" => Ok(Self::SouthWest),
+            "W" => Ok(Self::West),
+            "NW" => Ok(Self::NorthWest),
+            _ => Err(MyError::UnknownDirection(token.to_owned())),
+        }
+    }
+    fn unit(self) -> Vec2D {
+        match self {
+            Self::North => Vec2D::new(0, 1),
+            Self::NorthEast => Vec2D::new(1, 1),
+            Self::East => Vec2D::new(1, 0),
+            Self::SouthEast => Vec2D::new(1, -1),
+            Self::South => Vec2D::new(0, -1),
+            Self::SouthWest => Vec2D::new(-1, -1),
+            Self::West => Vec2D::new(-1, 0),
+            Self::NorthWest => Vec2D::new(-1, 1),
+        }
+    }
+}
+
+/// `U`, `D`, `L`, `R` and `F`, `B` for forward and back in space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Spatial {
+    Up,
+    Down,
+    Left,
+    Right,
+    Forward,
+    Back,
+}
+
+impl Alphabet for Spatial {
+    type Vector = Vec3D;
+    fn parse(token: &str) -> Result<Self, MyError> {
+        match single_char(token)? {
+            'U' => Ok(Self::Up),
+            'D' => Ok(Self::Down),
+            'L' => Ok(Self::Left),
+            'R' => Ok(Self::Right),
+            'F' => Ok(Self::Forward),
+            'B' => Ok(Self::Back),
+            _ => Err(MyError::UnknownDirection(token.to_owned())),
+        }
+    }
+    fn unit(self) -> Vec3D {
+        match self {
+            Self::Up => Vec3D::new(0, 1, 0),
+            Self::Down => Vec3D::new(0, -1, 0),
+            Self::Left => Vec3D::new(-1, 0, 0),
+            Self::Right => Vec3D::new(1, 0, 0),
+            Self::Forward => Vec3D::new(0, 0, 1),
+            Self::Back => Vec3D::new(0, 0, -1),
+        }
+    }
+}
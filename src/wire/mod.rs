@@ -0,0 +1,473 @@
+mod direction;
+mod render;
+mod vector;
+
+pub use self::direction::{Alphabet, Compass, Direction, Spatial};
+pub use self::render::{render_ascii, render_svg};
+pub use self::vector::{Vec2D, Vec3D, Vector};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::convert::TryFrom;
+use std::num::ParseIntError;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MyError {
+    #[error("encountered unknown direction `{0}`")]
+    UnknownDirection(String),
+    #[error("encountered bad movement syntax for `{0}`")]
+    BadMovementSyntax(String),
+    #[error("encountered invalid movement length: {0}")]
+    InvalidMovementLength(#[from] ParseIntError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Movement<D = Direction> {
+    pub direction: D,
+    pub length: u32,
+}
+
+impl<D: Alphabet> TryFrom<&str> for Movement<D> {
+    type Error = MyError;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let n = s
+            .find(|c: char| c.is_ascii_digit())
+            .filter(|&n| n > 0)
+            .ok_or_else(|| MyError::BadMovementSyntax(s.to_owned()))?;
+        let direction = D::parse(&s[..n])?;
+        let length = s[n..].parse()?;
+        Ok(Self { direction, length })
+    }
+}
+
+impl<D: Alphabet> Movement<D> {
+    pub fn to_vector(&self) -> D::Vector {
+        self.direction.unit().scale(i64::from(self.length))
+    }
+}
+
+/// A straight piece of a wire from `start` to `end`, both included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment<V = Vec2D> {
+    pub start: V,
+    pub end: V,
+    /// The direction of a single step.
+    pub unit: V,
+    /// Steps along the wire from the origin to `start`.
+    pub steps: u64,
+}
+
+/// The line through `p` in direction `unit` as its direction, with the first
+/// non-zero component positive, and its point where that component is zero.
+fn line_key<V: Vector>(p: V, unit: V) -> (V, V) {
+    match (0..V::DIMENSIONS).find(|&i| unit.component(i) != 0) {
+        Some(i) => {
+            let unit = unit.scale(unit.component(i));
+            (unit, p - unit.scale(p.component(i)))
+        }
+        None => (unit, p),
+    }
+}
+
+impl<V: Vector> Segment<V> {
+    pub fn len(&self) -> u64 {
+        self.position(self.end).unwrap_or(0) as u64
+    }
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+    fn at(&self, t: i64) -> V {
+        self.start + self.unit.scale(t)
+    }
+    /// How many steps `p` is from `start` along the line of the segment,
+    /// which may be outside the segment itself, or `None` if `p` is off the line.
+    fn position(&self, p: V) -> Option<i64> {
+        let (unit, base) = line_key(p, self.unit);
+        if (unit, base) != line_key(self.start, self.unit) {
+            return None;
+        }
+        let i = (0..V::DIMENSIONS).find(|&i| self.unit.component(i) != 0);
+        Some(i.map_or(0, |i| {
+            (p - self.start).component(i) * self.unit.component(i)
+        }))
+    }
+    /// How many steps `p` is from `start`, if it is on the segment.
+    fn offset(&self, p: V) -> Option<i64> {
+        let t = self.position(p)?;
+        if t >= 0 && t <= self.position(self.end)? {
+            Some(t)
+        } else {
+            None
+        }
+    }
+    pub fn contains(&self, p: V) -> bool {
+        self.offset(p).is_some()
+    }
+    /// Steps along the wire to a point on this segment.
+    pub fn steps_to(&self, p: V) -> u64 {
+        self.steps + self.offset(p).unwrap_or(0) as u64
+    }
+    /// The part both segments share, if any, as offsets from `start`.
+    /// Segments that aren't parallel share at most a single point.
+    pub fn overlap(&self, other: &Self) -> Option<(i64, i64)> {
+        if self.is_empty() {
+            return other.offset(self.start).map(|_| (0, 0));
+        }
+        let parallel = line_key(V::zero(), self.unit).0 == line_key(V::zero(), other.unit).0;
+        if parallel || other.is_empty() {
+            let t0 = self.position(other.start)?;
+            let t1 = self.position(other.end)?;
+            let len = self.position(self.end)?;
+            let (min, max) = (t0.min(t1).max(0), t0.max(t1).min(len));
+            return if min <= max { Some((min, max)) } else { None };
+        }
+        // solve start + t * unit = other.start + s * other.unit on two
+        // components for which the directions are independent
+        let (a, b, d) = (self.unit, other.unit, other.start - self.start);
+        for i in 0..V::DIMENSIONS {
+            for j in i + 1..V::DIMENSIONS {
+                let det = b.component(i) * a.component(j) - a.component(i) * b.component(j);
+                if det == 0 {
+                    continue;
+                }
+                let t = b.component(i) * d.component(j) - d.component(i) * b.component(j);
+                if t % det != 0 {
+                    return None;
+                }
+                let p = self.at(t / det);
+                return match (self.offset(p), other.offset(p)) {
+                    (Some(t), Some(_)) => Some((t, t)),
+                    _ => None,
+                };
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wire<D = Direction> {
+    pub movements: Vec<Movement<D>>,
+}
+
+impl<D: Alphabet> FromStr for Wire<D> {
+    type Err = MyError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            movements: s
+                .trim()
+                .split(',')
+                .map(Movement::try_from)
+                .collect::<Result<_, MyError>>()?,
+        })
+    }
+}
+
+impl<D: Alphabet> Wire<D> {
+    /// The straight pieces of the wire starting at the origin, one per movement.
+    pub fn segments(&self) -> Vec<Segment<D::Vector>> {
+        let mut start = D::Vector::zero();
+        let mut steps = 0;
+        self.movements
+            .iter()
+            .map(|movement| {
+                let end = start + movement.to_vector();
+                let unit = movement.direction.unit();
+                let segment = Segment {
+                    start,
+                    end,
+                    unit,
+                    steps,
+                };
+                start = end;
+                steps += u64::from(movement.length);
+                segment
+            })
+            .collect()
+    }
+}
+
+/// Looks up how many steps a wire takes to first reach a point.
+///
+/// The wire is walked once to split it into segments, which are then found
+/// by the line they lie on, so lookups don't depend on the length of the wire.
+#[derive(Debug, Clone)]
+pub struct WireIndex<V = Vec2D> {
+    segments: Vec<Segment<V>>,
+    /// Indices of the segments lying on each line, see `line_key`.
+    lines: HashMap<(V, V), Vec<usize>>,
+    /// The directions of all lines.
+    directions: BTreeSet<V>,
+}
+
+impl<V: Vector> WireIndex<V> {
+    pub fn new<D: Alphabet<Vector = V>>(wire: &Wire<D>) -> Self {
+        let segments = wire.segments();
+        let mut lines: HashMap<_, Vec<_>> = HashMap::new();
+        let mut directions = BTreeSet::new();
+        for (i, segment) in segments.iter().enumerate() {
+            let key = line_key(segment.start, segment.unit);
+            directions.insert(key.0);
+            lines.entry(key).or_default().push(i);
+        }
+        Self {
+            segments,
+            lines,
+            directions,
+        }
+    }
+    pub fn segments(&self) -> &[Segment<V>] {
+        &self.segments
+    }
+    /// The steps along the wire to its first visit of `p`, if it passes `p`.
+    pub fn first_visit(&self, p: V) -> Option<u64> {
+        self.directions
+            .iter()
+            .filter_map(|&unit| self.lines.get(&line_key(p, unit)))
+            .flatten()
+            .map(|&i| &self.segments[i])
+            .filter(|segment| segment.contains(p))
+            .map(|segment| segment.steps_to(p))
+            .min()
+    }
+    pub fn contains(&self, p: V) -> bool {
+        self.first_visit(p).is_some()
+    }
+}
+
+/// A point where two wires meet, with the steps each wire takes to get there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crossing<V = Vec2D> {
+    pub point: V,
+    pub steps: (u64, u64),
+}
+
+/// The offsets between `min` and `max` along the segment worth reporting: the
+/// ends and the points closest to the origin, leaving out the origin itself.
+fn candidates<V: Vector>(segment: &Segment<V>, min: i64, max: i64) -> Vec<i64> {
+    let mut offsets = vec![min, max];
+    // the distance to the origin only changes its slope where a component is zero
+    for i in 0..V::DIMENSIONS {
+        let u = segment.unit.component(i);
+        if u != 0 {
+            offsets.push((-segment.start.component(i) * u).max(min).min(max));
+        }
+    }
+    if let Some(t) = offsets.iter().find(|&&t| segment.at(t) == V::zero()) {
+        // the line passes through the origin, report its neighbours instead
+        offsets.extend(&[t - 1, t + 1]);
+    }
+    offsets.sort();
+    offsets.dedup();
+    offsets.retain(|&t| t >= min && t <= max && segment.at(t) != V::zero());
+    offsets
+}
+
+/// Where the two wires meet, apart from the origin, compared segment by segment.
+///
+/// Where the wires run along each other, only the ends of the shared part and
+/// its point closest to the origin are reported. Both the distance to the
+/// origin and the combined steps are smallest at one of those points, so the
+/// closest crossing and the one with the fewest steps are always included.
+/// Steps count along the segments involved, so a point a wire passes more
+/// than once may be reported with the steps of each visit.
+pub fn crossings<D: Alphabet>(a: &Wire<D>, b: &Wire<D>) -> Vec<Crossing<D::Vector>> {
+    segment_crossings(&a.segments(), &b.segments())
+}
+
+fn segment_crossings<V: Vector>(a: &[Segment<V>], b: &[Segment<V>]) -> Vec<Crossing<V>> {
+    let mut crossings = Vec::new();
+    for sa in a {
+        for sb in b {
+            if let Some((min, max)) = sa.overlap(sb) {
+                crossings.extend(candidates(sa, min, max).into_iter().map(|t| {
+                    let point = sa.at(t);
+                    Crossing {
+                        point,
+                        steps: (sa.steps + t as u64, sb.steps_to(point)),
+                    }
+                }));
+            }
+        }
+    }
+    crossings
+}
+
+/// A point where several wires meet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Junction<V = Vec2D> {
+    pub point: V,
+    /// The fewest steps each wire passing the point takes to get there,
+    /// by index of the wire.
+    pub steps: BTreeMap<usize, u64>,
+}
+
+impl<V> Junction<V> {
+    /// The number of wires meeting at the point.
+    pub fn wires(&self) -> usize {
+        self.steps.len()
+    }
+    /// The combined steps of all wires meeting at the point.
+    pub fn total_steps(&self) -> u64 {
+        self.steps.values().sum()
+    }
+}
+
+/// Points where at least `k` wires meet, apart from the origin, in order.
+///
+/// The points considered are those reported by `crossings` for any pair of
+/// wires, so along wires running on top of each other only the ends of the
+/// shared part and its point closest to the origin are included.
+pub fn junctions<D: Alphabet>(wires: &[Wire<D>], k: usize) -> Vec<Junction<D::Vector>> {
+    let indexes: Vec<_> = wires.iter().map(WireIndex::new).collect();
+    let mut points = BTreeSet::new();
+    for (i, a) in indexes.iter().enumerate() {
+        for b in &indexes[i + 1..] {
+            let crossings = segment_crossings(a.segments(), b.segments());
+            points.extend(crossings.into_iter().map(|c| c.point));
+        }
+    }
+    points
+        .into_iter()
+        .map(|point| Junction {
+            point,
+            steps: indexes
+                .iter()
+                .enumerate()
+                .filter_map(|(wire, index)| Some((wire, index.first_visit(point)?)))
+                .collect(),
+        })
+        .filter(|junction| junction.wires() >= k)
+        .collect()
+}
+
+/// The pairs of wires that meet somewhere apart from the origin.
+pub fn intersecting_pairs<D: Alphabet>(wires: &[Wire<D>]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (i, a) in wires.iter().enumerate() {
+        for (j, b) in wires.iter().enumerate().skip(i + 1) {
+            if !crossings(a, b).is_empty() {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+/// Parses one wire per line.
+pub fn parse_wires<D: Alphabet>(s: &str) -> Result<Vec<Wire<D>>, MyError> {
+    s.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const EXAMPLES: [(&str, u64, u64); 3] = [
+        ("R8,U5,L5,D3\nU7,R6,D4,L4", 6, 30),
+        (
+            "R75,D30,R83,U83,L12,D49,R71,U7,L72\nU62,R66,U55,R34,D71,R55,D58,R83",
+            159,
+            610,
+        ),
+        (
+            "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51\n\
+             U98,R91,D20,R16,D67,R40,U7,R15,U6,R7",
+            135,
+            410,
+        ),
+    ];
+    #[test]
+    fn examples() {
+        for &(input, distance, steps) in &EXAMPLES {
+            let wires: Vec<Wire> = parse_wires(input).unwrap();
+            let crossings = crossings(&wires[0], &wires[1]);
+            let closest = crossings.iter().map(|c| c.point.l1_norm()).min();
+            let fewest = crossings.iter().map(|c| c.steps.0 + c.steps.1).min();
+            assert_eq!((closest, fewest), (Some(distance), Some(steps)));
+        }
+    }
+    #[test]
+    fn first_visits() {
+        let wire: Wire = "R8,U5,L5,D3,R10".parse().unwrap();
+        let index = WireIndex::new(&wire);
+        assert_eq!(index.first_visit(Vec2D::new(0, 0)), Some(0));
+        assert_eq!(index.first_visit(Vec2D::new(8, 3)), Some(11));
+        // passed at step 10 and again at step 26
+        assert_eq!(index.first_visit(Vec2D::new(8, 2)), Some(10));
+        assert_eq!(index.first_visit(Vec2D::new(7, 2)), Some(25));
+        assert!(!index.contains(Vec2D::new(1, 1)));
+    }
+    #[test]
+    fn several_wires() {
+        let wires: Vec<Wire> = parse_wires("R8,U5,L5,D3\nU7,R6,D4,L4\nU3,R10\nD5").unwrap();
+        assert_eq!(intersecting_pairs(&wires), vec![(0, 1), (0, 2), (1, 2)]);
+        let junctions = junctions(&wires, 3);
+        assert_eq!(junctions.len(), 1);
+        assert_eq!(junctions[0].point, Vec2D::new(3, 3));
+        // wire 2 gets there in 3 + 3 steps
+        assert_eq!(junctions[0].total_steps(), 20 + 20 + 6);
+        let all = super::junctions(&wires, 2);
+        assert_eq!(all.len(), 7);
+        // wires 1 and 2 both start upwards
+        assert_eq!(all[0].point, Vec2D::new(0, 1));
+        assert_eq!(all[0].total_steps(), 2);
+    }
+    #[test]
+    fn diagonal_wires() {
+        let wires: Vec<Wire<Compass>> = parse_wires("NE4,E2\nE2,N6,SE2\nNW1,NE3").unwrap();
+        // wires 0 and 2 run parallel two steps apart and never meet
+        assert_eq!(intersecting_pairs(&wires), vec![(0, 1), (1, 2)]);
+        let junctions = junctions(&wires, 2);
+        let points: Vec<_> = junctions.iter().map(|j| j.point).collect();
+        assert_eq!(
+            points,
+            vec![Vec2D::new(2, 2), Vec2D::new(2, 4), Vec2D::new(4, 4)]
+        );
+        let steps: Vec<_> = junctions[1].steps.iter().map(|(&w, &s)| (w, s)).collect();
+        assert_eq!(steps, vec![(1, 6), (2, 4)]);
+        // diagonals crossing between grid points don't meet
+        let wires: Vec<Wire<Compass>> = parse_wires("NE1\nN1,SE1").unwrap();
+        assert!(crossings(&wires[0], &wires[1]).is_empty());
+    }
+    #[test]
+    fn wires_in_space() {
+        let wires: Vec<Wire<Spatial>> = parse_wires("R3,F2,U1\nF2,R5,B2,U9\nU2,R3,F2,D4").unwrap();
+        let junctions = junctions(&wires, 2);
+        let points: Vec<_> = junctions.iter().map(|j| j.point).collect();
+        assert_eq!(points, vec![Vec3D::new(3, 0, 2), Vec3D::new(3, 1, 2)]);
+        assert_eq!(junctions[0].wires(), 3);
+        assert_eq!(junctions[0].total_steps(), 5 + 5 + 9);
+        assert_eq!(junctions[1].wires(), 2);
+    }
+    #[test]
+    fn parse_errors() {
+        let messages: Vec<_> = ["U", "X5", "5", "NE1"]
+            .iter()
+            .map(|s| s.parse::<Wire>().unwrap_err().to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "encountered bad movement syntax for `U`",
+                "encountered unknown direction `X`",
+                "encountered bad movement syntax for `5`",
+                "encountered unknown direction `NE`",
+            ]
+        );
+    }
+    #[test]
+    fn overlapping_wires() {
+        // both wires run along the x axis through the origin
+        let wires: Vec<Wire> = parse_wires("L3,R10\nR5,U1,L9,D1,R2").unwrap();
+        let crossings = crossings(&wires[0], &wires[1]);
+        let closest = crossings.iter().map(|c| c.point.l1_norm()).min();
+        assert_eq!(closest, Some(1));
+        assert!(crossings.iter().all(|c| c.point != Vec2D::new(0, 0)));
+        let fewest = crossings.iter().map(|c| c.steps.0 + c.steps.1).min();
+        // (1, 0) is reached after 7 and 1 steps
+        assert_eq!(fewest, Some(8));
+    }
+}
@@ -0,0 +1,158 @@
+use super::{Alphabet, Segment, Vec2D, Wire};
+use std::fmt::Write;
+
+const WIRE_SYMBOLS: &[u8] = b"123456789abcdefghijklmnopqrstuvwxyz";
+const COLORS: [&str; 6] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#9467bd", "#8c564b", "#e377c2",
+];
+
+/// Smallest and largest coordinates of the wires and the origin.
+fn bounds(segments: &[Vec<Segment>]) -> (Vec2D, Vec2D) {
+    let origin = Vec2D::new(0, 0);
+    let points = segments.iter().flatten().flat_map(|s| vec![s.start, s.end]);
+    points.fold((origin, origin), |(min, max), p| {
+        (
+            Vec2D::new(min.x.min(p.x), min.y.min(p.y)),
+            Vec2D::new(max.x.max(p.x), max.y.max(p.y)),
+        )
+    })
+}
+
+/// Draws the wires on a character grid of at most `width` by `height`
+/// characters, with up pointing up. Each character covers a square of the
+/// same number of points, so long wires are scaled down to fit.
+///
+/// Wires are drawn as `1`, `2`, ..., cells used by several wires as `+`,
+/// the origin as `o` and the highlighted points as `X`.
+pub fn render_ascii<D: Alphabet<Vector = Vec2D>>(
+    wires: &[Wire<D>],
+    highlights: &[Vec2D],
+    width: usize,
+    height: usize,
+) -> String {
+    let segments: Vec<_> = wires.iter().map(Wire::segments).collect();
+    let (min, max) = bounds(&segments);
+    let span = |lo: i64, hi: i64, cells: usize| (hi - lo) as u64 / cells.max(1) as u64 + 1;
+    let scale = span(min.x, max.x, width).max(span(min.y, max.y, height)) as i64;
+    let cell = |p: Vec2D| {
+        (
+            ((p.x - min.x) / scale) as usize,
+            ((max.y - p.y) / scale) as usize,
+        )
+    };
+    let (columns, rows) = cell(Vec2D::new(max.x, min.y));
+    let mut grid = vec![vec![b' '; columns + 1]; rows + 1];
+    for (wire, segments) in segments.iter().enumerate() {
+        let symbol = WIRE_SYMBOLS[wire % WIRE_SYMBOLS.len()];
+        for segment in segments {
+            // one point per character the segment passes
+            let len = segment.len() as i64;
+            let offsets = (0..len).step_by(scale as usize).chain(Some(len));
+            for (x, y) in offsets.map(|t| cell(segment.at(t))) {
+                grid[y][x] = match grid[y][x] {
+                    b' ' => symbol,
+                    c if c == symbol => symbol,
+                    _ => b'+',
+                };
+            }
+        }
+    }
+    for &p in highlights {
+        let (x, y) = cell(p);
+        if let Some(c) = grid.get_mut(y).and_then(|row| row.get_mut(x)) {
+            *c = b'X';
+        }
+    }
+    let (x, y) = cell(Vec2D::new(0, 0));
+    grid[y][x] = b'o';
+    let mut out = String::new();
+    for row in grid {
+        out.push_str(String::from_utf8_lossy(&row).trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Draws the wires as SVG, each as a polyline in its own color, the origin
+/// as a black dot and the highlighted points as red circles. Line widths
+/// don't scale, so even very long wires stay visible.
+pub fn render_svg<D: Alphabet<Vector = Vec2D>>(wires: &[Wire<D>], highlights: &[Vec2D]) -> String {
+    let segments: Vec<_> = wires.iter().map(Wire::segments).collect();
+    let (min, max) = bounds(&segments);
+    let margin = ((max.x - min.x).max(max.y - min.y) / 50).max(1);
+    let radius = margin as f64 / 2.0;
+    // SVG coordinates grow downwards, so y is negated
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+        min.x - margin,
+        -max.y - margin,
+        max.x - min.x + 2 * margin,
+        max.y - min.y + 2 * margin
+    );
+    for (wire, segments) in segments.iter().enumerate() {
+        let mut points = vec!["0,0".to_string()];
+        points.extend(segments.iter().map(|s| format!("{},{}", s.end.x, -s.end.y)));
+        let _ = writeln!(
+            out,
+            r#"  <polyline points="{}" fill="none" stroke="{}" stroke-width="2" vector-effect="non-scaling-stroke"/>"#,
+            points.join(" "),
+            COLORS[wire % COLORS.len()]
+        );
+    }
+    for p in highlights {
+        let _ = writeln!(
+            out,
+            r#"  <circle cx="{}" cy="{}" r="{}" fill="none" stroke="red" stroke-width="2" vector-effect="non-scaling-stroke"/>"#,
+            p.x, -p.y, radius
+        );
+    }
+    let _ = writeln!(
+        out,
+        r#"  <circle cx="0" cy="0" r="{}" fill="black"/>"#,
+        radius
+    );
+    out.push_str("</svg>\n");
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{junctions, parse_wires, Compass};
+    use super::*;
+    #[test]
+    fn ascii() {
+        let wires: Vec<Wire> = parse_wires("R8,U5,L5,D3\nU7,R6,D4,L4").unwrap();
+        let highlights: Vec<_> = junctions(&wires, 2).iter().map(|j| j.point).collect();
+        let expected = "\
+2222222
+2     2
+2  111X11
+2  1  2 1
+2 2X222 1
+2  1    1
+2       1
+o11111111";
+        assert_eq!(
+            render_ascii(&wires, &highlights, 80, 40).trim_end(),
+            expected
+        );
+        let scaled = render_ascii(&wires, &highlights, 4, 4);
+        assert_eq!(scaled.lines().count(), 3);
+        assert!(scaled.lines().all(|line| line.len() <= 4));
+
+        let wires: Vec<Wire<Compass>> = parse_wires("NE3,S3\nE3,NW2").unwrap();
+        let expected = "   1\n 211\n 121\no22+";
+        assert_eq!(render_ascii(&wires, &[], 80, 40).trim_end(), expected);
+    }
+    #[test]
+    fn svg() {
+        let wires: Vec<Wire> = parse_wires("R8,U5,L5,D3\nU7,R6,D4,L4").unwrap();
+        let svg = render_svg(&wires, &[Vec2D::new(3, 3)]);
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-1 -8 10 9">"#));
+        assert!(svg.contains(r#"points="0,0 8,0 8,-5 3,-5 3,-2""#));
+        assert!(svg.contains(r#"<circle cx="3" cy="-3" r="0.5""#));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Add, Sub};
+
+/// A point or offset on an integer grid of any dimension.
+pub trait Vector:
+    Copy + Eq + Ord + Hash + fmt::Debug + fmt::Display + Add<Output = Self> + Sub<Output = Self>
+{
+    const DIMENSIONS: usize;
+    fn zero() -> Self;
+    fn component(&self, i: usize) -> i64;
+    fn scale(self, k: i64) -> Self;
+    /// The Manhattan distance to the origin.
+    fn l1_norm(&self) -> u64 {
+        (0..Self::DIMENSIONS)
+            .map(|i| self.component(i).unsigned_abs())
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vec2D {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Vec2D {
+    pub fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+    pub fn l1_norm(&self) -> u64 {
+        Vector::l1_norm(self)
+    }
+}
+
+impl Vector for Vec2D {
+    const DIMENSIONS: usize = 2;
+    fn zero() -> Self {
+        Self::new(0, 0)
+    }
+    fn component(&self, i: usize) -> i64 {
+        [self.x, self.y][i]
+    }
+    fn scale(self, k: i64) -> Self {
+        Self::new(self.x * k, self.y * k)
+    }
+}
+
+impl Add<Self> for Vec2D {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+impl Sub<Self> for Vec2D {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+}
+
+impl fmt::Display for Vec2D {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vec3D {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Vec3D {
+    pub fn new(x: i64, y: i64, z: i64) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl Vector for Vec3D {
+    const DIMENSIONS: usize = 3;
+    fn zero() -> Self {
+        Self::new(0, 0, 0)
+    }
+    fn component(&self, i: usize) -> i64 {
+        [self.x, self.y, self.z][i]
+    }
+    fn scale(self, k: i64) -> Self {
+        Self::new(self.x * k, self.y * k, self.z * k)
+    }
+}
+
+impl Add<Self> for Vec3D {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        }
+    }
+}
+
+impl Sub<Self> for Vec3D {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+        }
+    }
+}
+
+impl fmt::Display for Vec3D {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}